@@ -1,4 +1,1536 @@
 <?xml version="1.0" encoding="UTF-8"?>
 <tileset version="1.2" tiledversion="2018.11.29" name="topdown" tilewidth="64" tileheight="64" tilecount="540" columns="27">
  <image source="/tilesheet_complete.png" width="1728" height="1280"/>
+ <tile id="117">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="118">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="119">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="120">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="121">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="122">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="123">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="124">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="125">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="128">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="129">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="130">
+  <properties>
+   <property name="material" value="sandbag"/>
+  </properties>
+ </tile>
+ <tile id="144">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="145">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="146">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="147">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="148">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="149">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="150">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="151">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="152">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="155">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+  <objectgroup draworder="index">
+   <object id="1" x="6" y="6" width="52" height="52"/>
+  </objectgroup>
+ </tile>
+ <tile id="156">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="157">
+  <properties>
+   <property name="material" value="sandbag"/>
+  </properties>
+ </tile>
+ <tile id="171">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="172">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="173">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="174">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="175">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="176">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="177">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="178">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="179">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="180">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="181">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="182">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="183">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="184">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="185">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="198">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="199">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="200">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="201">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="202">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="203">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="204">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="205">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="206">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="207">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="208">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="209">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="210">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="211">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="212">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="225">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="226">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="227">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="228">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="229">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="230">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="231">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="232">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="233">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="234">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="235">
+  <properties>
+   <property name="collidable" type="bool" value="false"/>
+   <property name="destructible" type="bool" value="false"/>
+  </properties>
+ </tile>
+ <tile id="236">
+  <properties>
+   <property name="hp" type="float" value="2"/>
+   <property name="mass" type="float" value="30"/>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="237">
+  <properties>
+   <property name="hp" type="float" value="2"/>
+   <property name="mass" type="float" value="30"/>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="238">
+  <properties>
+   <property name="hp" type="float" value="2"/>
+   <property name="mass" type="float" value="30"/>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="252">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="253">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="254">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="255">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="256">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="257">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="258">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="259">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="260">
+  <properties>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="270">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="271">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="272">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="273">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="274">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="275">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="276">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="277">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="278">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="279">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="280">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="281">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="282">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="283">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="284">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="285">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="286">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="287">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="295">
+  <properties>
+   <property name="material" value="metal"/>
+  </properties>
+ </tile>
+ <tile id="296">
+  <properties>
+   <property name="material" value="metal"/>
+  </properties>
+ </tile>
+ <tile id="297">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="298">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="299">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="300">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="301">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="302">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="303">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="304">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="305">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="306">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="307">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="308">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="309">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="310">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="311">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="312">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="313">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="314">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="315">
+  <properties>
+   <property name="breaking_sound" value="/sound/barrel_break.wav"/>
+   <property name="mass" type="float" value="8"/>
+   <property name="material" value="wood"/>
+   <property name="restitution" type="float" value="0.5"/>
+   <property name="shape" value="ball"/>
+  </properties>
+ </tile>
+ <tile id="316">
+  <properties>
+   <property name="breaking_sound" value="/sound/barrel_break.wav"/>
+   <property name="mass" type="float" value="8"/>
+   <property name="material" value="wood"/>
+   <property name="restitution" type="float" value="0.5"/>
+   <property name="shape" value="ball"/>
+  </properties>
+ </tile>
+ <tile id="317">
+  <properties>
+   <property name="breaking_sound" value="/sound/barrel_break.wav"/>
+   <property name="mass" type="float" value="8"/>
+   <property name="material" value="wood"/>
+   <property name="restitution" type="float" value="0.5"/>
+   <property name="shape" value="ball"/>
+  </properties>
+ </tile>
+ <tile id="320">
+  <properties>
+   <property name="material" value="metal"/>
+  </properties>
+ </tile>
+ <tile id="322">
+  <properties>
+   <property name="material" value="metal"/>
+  </properties>
+ </tile>
+ <tile id="323">
+  <properties>
+   <property name="material" value="metal"/>
+  </properties>
+ </tile>
+ <tile id="324">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="325">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="326">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="327">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="328">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="329">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="330">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="331">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="332">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="333">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="334">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="335">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="336">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="337">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="338">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="339">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="340">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="341">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="351">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="352">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="353">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="354">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="355">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="356">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="357">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="358">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="359">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="360">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="361">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="362">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="363">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="364">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="365">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="366">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="367">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="368">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="378">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="379">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="380">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="381">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="382">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="383">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="384">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="385">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="386">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="387">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="388">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="389">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="390">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="391">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="392">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="393">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="394">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="395">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="405">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="406">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="407">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="408">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="409">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="410">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="411">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="412">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="413">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="414">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="415">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="416">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="417">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="418">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="419">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="420">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="421">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="422">
+  <properties>
+   <property name="material" value="stone"/>
+  </properties>
+ </tile>
+ <tile id="432">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="433">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="434">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="435">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="436">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="437">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="438">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="439">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="440">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="441">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="442">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="443">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="444">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="445">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="446">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="447">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="448">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="449">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="450">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="459">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="460">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="461">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="462">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="463">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="464">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="465">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="466">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="467">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="468">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="469">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="470">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="471">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="472">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="473">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="474">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="475">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="476">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="477">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="486">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="487">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="488">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="489">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="490">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="491">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="492">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="493">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="494">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="495">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="496">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="497">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="498">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="499">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="500">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="501">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="502">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="503">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="504">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="513">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="514">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="515">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="516">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="517">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="518">
+  <properties>
+   <property name="hp" type="float" value="0.5"/>
+   <property name="mass" type="float" value="4"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="519">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="520">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="521">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="522">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="523">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="524">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="525">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="526">
+  <properties>
+   <property name="hp" type="float" value="0.75"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="527">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="528">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="529">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="530">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
+ <tile id="531">
+  <properties>
+   <property name="friction" type="float" value="0.4"/>
+   <property name="mass" type="float" value="6"/>
+   <property name="material" value="wood"/>
+  </properties>
+ </tile>
 </tileset>
@@ -1,29 +1,135 @@
 use super::consts::*;
 use super::types::*;
 
+use ncollide2d::query::Ray;
+use ncollide2d::world::CollisionGroups;
+use nphysics2d::object::{BodyHandle, ColliderHandle};
+use nphysics2d::world::World;
 use rand::Rng;
 use std::time::{Duration, Instant};
 
+// Closest collider along the ray from `from` towards `to`, ignoring one collision group
+fn nearest_ray_hit(
+    world: &World<f32>,
+    from: Point2,
+    to: Point2,
+    ignore_group: usize,
+) -> Option<(ColliderHandle, f32, f32)> {
+    let collision_world = world.collision_world();
+
+    let mut groups = CollisionGroups::new();
+    groups.set_blacklist(&[ignore_group]);
+
+    let offset = to - from;
+    let offset_len = offset.norm();
+    if offset_len < 1e-5 {
+        return None;
+    }
+
+    let ray = Ray {
+        origin: from,
+        dir: offset * (1.0 / offset_len),
+    };
+
+    collision_world
+        .interferences_with_ray(&ray, &groups)
+        .map(|(collider, collision)| (collider.handle(), collision.toi))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(handle, toi)| (handle, toi, offset_len))
+}
+
+// Whether a pawn-sized target at `to` is the first thing hit when looking from `from`
+pub fn is_point_visible(world: &World<f32>, from: Point2, to: Point2, ignore_group: usize) -> bool {
+    if let Some((_, min_toi, offset_len)) = nearest_ray_hit(world, from, to, ignore_group) {
+        (min_toi - offset_len).abs() < 0.5
+    } else {
+        false
+    }
+}
+
+// Like `is_point_visible`, but for targets of any size
+pub fn is_body_visible(
+    world: &World<f32>,
+    from: Point2,
+    to: Point2,
+    body: BodyHandle,
+    ignore_group: usize,
+) -> bool {
+    nearest_ray_hit(world, from, to, ignore_group).map_or(false, |(collider, _, _)| {
+        world.collider_body_handle(collider) == Some(body)
+    })
+}
+
+// What an enemy's AI is up to, shown by the round's debug overlay
+#[derive(Default)]
+pub struct AiDebug {
+    pub state: String,
+    pub target: Option<Point2>,
+    pub path: Vec<Point2>,
+    // Last line of sight check, and whether it reached the player
+    pub sight: Option<(Point2, Point2, bool)>,
+}
+
 pub trait AiBehavior {
     fn update(&mut self, rb: &RigidBody<f32>) -> Movement;
+    fn debug(&self) -> AiDebug {
+        AiDebug::default()
+    }
+    fn set_objective(&mut self, _objective: Option<Point2>) {}
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct DozerBehaviorConfig {
+    pub outer_radius: f32,
+    pub idle_min_ms: u64,
+    pub idle_max_ms: u64,
+    pub ram_min_ms: u64,
+    pub ram_max_ms: u64,
+}
+
+impl Default for DozerBehaviorConfig {
+    fn default() -> Self {
+        Self {
+            outer_radius: DOZER_OUTER_RADIUS,
+            idle_min_ms: 1000,
+            idle_max_ms: 2000,
+            ram_min_ms: 1000,
+            ram_max_ms: 2000,
+        }
+    }
+}
+
+// `gen_range` panics on an empty range, which equal bounds in a config would give it
+fn random_duration(min_ms: u64, max_ms: u64) -> Duration {
+    if max_ms > min_ms {
+        Duration::from_millis(rand::thread_rng().gen_range(min_ms, max_ms))
+    } else {
+        Duration::from_millis(min_ms)
+    }
 }
 
 pub struct EnemyDozerBehavior {
+    cfg: DozerBehaviorConfig,
     state: DozerState,
     last_vel_mag: f32,
+    // The ranch, rammed until told otherwise
+    home: Point2,
 
     // TODO: acquire from list of alive wall pieces
     look_at: Point2,
 }
 
 impl EnemyDozerBehavior {
-    pub fn new() -> Self {
+    pub fn new(cfg: DozerBehaviorConfig, home: Point2) -> Self {
         Self {
+            cfg,
             state: DozerState::IdlingUntil(
-                Instant::now() + Duration::from_millis(rand::thread_rng().gen_range(1000, 2000)),
+                Instant::now() + random_duration(cfg.idle_min_ms, cfg.idle_max_ms),
             ),
             last_vel_mag: 0.0,
-            look_at: Point2::origin(),
+            home,
+            look_at: home,
         }
     }
 }
@@ -38,11 +144,9 @@ enum DozerState {
 
 impl AiBehavior for EnemyDozerBehavior {
     fn update(&mut self, rb: &RigidBody<f32>) -> Movement {
-        let mut rng = rand::thread_rng();
-
         let vel_mag = rb.velocity().linear.norm();
-        let pos = rb.position().translation.vector;
-        let dist_to_center = pos.norm();
+        let pos: Point2 = rb.position().translation.vector.into();
+        let dist_to_center = (pos - self.home).norm();
         let now = Instant::now();
 
         let mut movement = Movement::default();
@@ -55,9 +159,9 @@ impl AiBehavior for EnemyDozerBehavior {
             }
             DozerState::Ramming => {
                 // If we've lost a good chunk of velocity, assume we hit an obstacle, start backing away
-                if dist_to_center < DOZER_OUTER_RADIUS && vel_mag < self.last_vel_mag * 0.9 {
+                if dist_to_center < self.cfg.outer_radius && vel_mag < self.last_vel_mag * 0.9 {
                     self.state = DozerState::RammingUntil(
-                        now + Duration::from_millis(rng.gen_range(1000, 2000)),
+                        now + random_duration(self.cfg.ram_min_ms, self.cfg.ram_max_ms),
                     )
                 }
 
@@ -71,7 +175,7 @@ impl AiBehavior for EnemyDozerBehavior {
                 movement.forward = 1.0;
             }
             DozerState::BackingAway => {
-                if dist_to_center > DOZER_OUTER_RADIUS {
+                if dist_to_center > self.cfg.outer_radius {
                     self.state = DozerState::Ramming;
                 }
 
@@ -89,4 +193,23 @@ impl AiBehavior for EnemyDozerBehavior {
 
         movement
     }
+
+    fn debug(&self) -> AiDebug {
+        let state = match self.state {
+            DozerState::IdlingUntil(_) => "idling",
+            DozerState::Ramming => "ramming",
+            DozerState::RammingUntil(_) => "pushing",
+            DozerState::BackingAway => "backing away",
+        };
+
+        AiDebug {
+            state: state.to_string(),
+            target: Some(self.look_at),
+            ..AiDebug::default()
+        }
+    }
+
+    fn set_objective(&mut self, objective: Option<Point2>) {
+        self.look_at = objective.unwrap_or(self.home);
+    }
 }
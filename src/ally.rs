@@ -0,0 +1,139 @@
+use crate::{
+    clamp_norm, is_body_visible, Bullet, Characters, Enemy, Faction, FactionTable, Movement,
+    PawnInput, Player, Point2, Vector2, Weapon, WeaponConfig, World,
+};
+
+use super::player::VisualState;
+
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::Context;
+use rand::Rng;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Character and weapon for each ranch hand the radio can call in
+pub const RANCH_HANDS: &[(&str, &str)] = &[
+    ("man_brown", "resources/ranch_rifle.toml"),
+    ("man_old", "resources/shotgun.toml"),
+];
+
+const ALLY_HEALTH: f32 = 0.6;
+const ALLY_RANGE: f32 = 20.0;
+const ALLY_MOVE_SPEED: f32 = 0.75;
+const ALLY_GUARD_RADIUS: f32 = 2.5;
+
+pub struct Ally {
+    pub pawn: Player,
+    guard_offset: Vector2,
+}
+
+impl Ally {
+    pub fn new(
+        ctx: &mut Context,
+        world: &mut World<f32>,
+        character: &str,
+        weapon: &str,
+        pos: Point2,
+        characters: &Characters,
+        spritebatch: Rc<RefCell<SpriteBatch>>,
+    ) -> Self {
+        let pawn = Player::new(
+            world,
+            character,
+            ALLY_HEALTH,
+            Weapon::from_config(ctx, WeaponConfig::from_toml(weapon)),
+            pos,
+            Faction::Allies,
+            characters,
+            spritebatch,
+        );
+
+        // Each hand keeps to their own spot around the player
+        let a = rand::thread_rng().gen::<f32>() * std::f32::consts::PI * 2.0;
+
+        Ally {
+            pawn,
+            guard_offset: Vector2::new(a.cos(), a.sin()) * ALLY_GUARD_RADIUS,
+        }
+    }
+
+    pub fn alive(&self) -> bool {
+        self.pawn.alive()
+    }
+
+    pub fn damage(&mut self, amount: f32) {
+        self.pawn.damage(amount);
+    }
+
+    pub fn draw(&self) {
+        self.pawn.draw();
+    }
+
+    fn closest_visible_enemy(
+        &self,
+        enemies: &[Box<dyn Enemy>],
+        factions: &FactionTable,
+        world: &World<f32>,
+    ) -> Option<Point2> {
+        if self.pawn.status.blinded() {
+            return None;
+        }
+
+        let pos = self.pawn.positional.position;
+        let faction = self.pawn.faction();
+
+        enemies
+            .iter()
+            .filter(|enemy| factions.is_hostile(faction, enemy.faction()))
+            .filter_map(|enemy| {
+                let enemy_pos = enemy.positional().position;
+                let dist = (enemy_pos - pos).norm();
+                match enemy.rigid_body() {
+                    Some(body)
+                        if dist < ALLY_RANGE
+                            && is_body_visible(world, pos, enemy_pos, body, faction.group()) =>
+                    {
+                        Some((enemy_pos, dist))
+                    }
+                    _ => None,
+                }
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(enemy_pos, _)| enemy_pos)
+    }
+
+    pub fn update(
+        &mut self,
+        player_pos: Point2,
+        enemies: &[Box<dyn Enemy>],
+        factions: &FactionTable,
+        world: &mut World<f32>,
+        bullets_out: &mut Vec<Bullet>,
+    ) {
+        if self.alive() {
+            let pos = self.pawn.positional.position;
+            let target = self.closest_visible_enemy(enemies, factions, world);
+
+            let guard_pos = player_pos + self.guard_offset;
+            let offset = if (guard_pos - pos).norm() < 0.5 {
+                Vector2::zeros()
+            } else {
+                clamp_norm(guard_pos - pos, ALLY_MOVE_SPEED)
+            };
+
+            self.pawn.set_input(PawnInput {
+                movement: Movement {
+                    right: offset.x,
+                    forward: offset.y,
+                },
+                shoot: target.is_some(),
+                aim_pos: target.unwrap_or(guard_pos),
+            });
+        } else {
+            self.pawn.set_input(PawnInput::default());
+        }
+
+        self.pawn.set_visual(VisualState::Gun);
+        self.pawn.update(world, bullets_out);
+    }
+}
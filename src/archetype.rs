@@ -0,0 +1,410 @@
+use crate::{
+    graphics, pick_perch, AiBehavior, Ball, BehaviorTree, BodyHandle, Boss, BossConfig, Bulldozer,
+    Context, Cuboid, DozerBehaviorConfig, Enemy, EnemyDozerBehavior, Faction, Helicopter,
+    HelicopterConfig, Isometry2, K9Config, Material, MoraleConfig, PawnMovement, Player, Point2,
+    Positional, ShapeHandle, Sniper, SniperConfig, Swat, SwatBehaviorConfig, TreeBehavior, Vector2,
+    VehicleMovement, Volumetric, Weapon, WeaponConfig, World, WorldData, COLLIDER_MARGIN,
+    GROUP_AIR, K9, SWAT_BEHAVIOR_TREE,
+};
+
+use ncollide2d::world::CollisionGroups;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::rc::Rc;
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColliderShape {
+    Ball,
+    Box,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ColliderConfig {
+    pub shape: ColliderShape,
+    // Diameter of a ball, or the shorter side of a box stretched to the sprite's aspect ratio
+    pub size: f32,
+    pub density: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    // Airborne colliders pass over walls, pawns and vehicles but can still be shot
+    #[serde(default)]
+    pub airborne: bool,
+}
+
+impl ColliderConfig {
+    pub fn shape_handle(&self, aspect: Vector2) -> ShapeHandle<f32> {
+        let rad = self.size / 2.0;
+        match self.shape {
+            ColliderShape::Ball => ShapeHandle::new(Ball::new(rad)),
+            ColliderShape::Box => {
+                ShapeHandle::new(Cuboid::new(rad * aspect / aspect.x.min(aspect.y)))
+            }
+        }
+    }
+
+    pub fn material(&self) -> Material<f32> {
+        Material::new(self.restitution, self.friction)
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BodyConfig {
+    Vehicle {
+        sprite: String,
+        #[serde(default)]
+        movement: VehicleMovement,
+    },
+    Pawn {
+        character: String,
+        weapon: String,
+        #[serde(default)]
+        movement: PawnMovement,
+    },
+    Aircraft {},
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "behavior", rename_all = "snake_case")]
+pub enum AiConfig {
+    Dozer(DozerBehaviorConfig),
+    Swat(SwatBehaviorConfig),
+    Boss(BossConfig),
+    K9(K9Config),
+    Sniper(SniperConfig),
+    Helicopter(HelicopterConfig),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub health: f32,
+    pub collider: ColliderConfig,
+    pub body: BodyConfig,
+    pub ai: AiConfig,
+    #[serde(default)]
+    pub morale: MoraleConfig,
+    // Behavior tree in `resources/behaviors` driving dozers and SWAT instead of the built-in logic
+    #[serde(default)]
+    pub tree: Option<String>,
+    // Side the enemy fights for; see `resources/factions.toml`
+    #[serde(default)]
+    pub faction: Faction,
+}
+
+impl EnemyArchetype {
+    pub fn from_toml(path: &Path) -> Self {
+        Self::try_from_toml(path).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_from_toml(path: &Path) -> Result<Self, String> {
+        let mut file = std::fs::File::open(path)
+            .map_err(|err| format!("failed to open enemy archetype {}: {}", path.display(), err))?;
+        let mut toml = String::new();
+        file.read_to_string(&mut toml)
+            .map_err(|err| format!("failed to open enemy archetype {}: {}", path.display(), err))?;
+        toml::from_str(&toml).map_err(|err| {
+            format!(
+                "failed to parse enemy archetype {}: {}",
+                path.display(),
+                err
+            )
+        })
+    }
+}
+
+pub struct EnemyArchetypes {
+    archetypes: HashMap<String, EnemyArchetype>,
+    sprites: HashMap<String, Rc<graphics::Image>>,
+}
+
+impl EnemyArchetypes {
+    pub fn load(ctx: &mut Context, dir: &str) -> Self {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .expect(&format!("failed to open enemy directory {}", dir))
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut archetypes = HashMap::new();
+        let mut sprites = HashMap::new();
+
+        for path in paths {
+            let archetype = EnemyArchetype::from_toml(&path);
+            if let BodyConfig::Vehicle { ref sprite, .. } = archetype.body {
+                if !sprites.contains_key(sprite) {
+                    let image = graphics::Image::new(ctx, sprite)
+                        .expect(&format!("opening enemy sprite {}", sprite));
+                    sprites.insert(sprite.clone(), Rc::new(image));
+                }
+            }
+            archetypes.insert(archetype.name.clone(), archetype);
+        }
+
+        EnemyArchetypes {
+            archetypes,
+            sprites,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> &EnemyArchetype {
+        self.archetypes
+            .get(name)
+            .expect(&format!("unknown enemy archetype {}", name))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.archetypes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn sprite(&self, path: &str) -> Rc<graphics::Image> {
+        self.sprites[path].clone()
+    }
+}
+
+pub fn add_enemy_rigid_body(
+    world: &mut World<f32>,
+    collider: &ColliderConfig,
+    faction: Faction,
+    aspect: Vector2,
+    pos: Point2,
+    rotation: f32,
+) -> BodyHandle {
+    let geom = collider.shape_handle(aspect);
+    let inertia = geom.inertia(collider.density);
+    let center_of_mass = geom.center_of_mass();
+
+    let pos = Isometry2::new(Vector2::new(pos.x, pos.y), rotation);
+    let rb = world.add_rigid_body(pos, inertia, center_of_mass);
+
+    let collider_handle = world.add_collider(
+        COLLIDER_MARGIN,
+        geom.clone(),
+        rb,
+        Isometry2::identity(),
+        collider.material(),
+    );
+
+    let mut col_group = CollisionGroups::new();
+    col_group.set_membership(&[faction.group()]);
+    if collider.airborne {
+        // Nothing on the ground is a member of GROUP_AIR; rays still hit it
+        col_group.set_whitelist(&[GROUP_AIR]);
+    }
+    world
+        .collision_world_mut()
+        .set_collision_groups(collider_handle, col_group);
+
+    rb
+}
+
+fn spawn_vehicle(
+    ctx: &mut Context,
+    data: &mut WorldData,
+    archetype: &EnemyArchetype,
+    sprite: &str,
+    movement: VehicleMovement,
+    ai: DozerBehaviorConfig,
+    morale: MoraleConfig,
+    pos: Point2,
+    rotation: f32,
+) -> Bulldozer {
+    let image = data.archetypes.sprite(sprite);
+    let aspect = Vector2::new(image.width() as f32, image.height() as f32);
+    let rb = add_enemy_rigid_body(
+        &mut data.world,
+        &archetype.collider,
+        archetype.faction,
+        aspect,
+        pos,
+        rotation,
+    );
+
+    let mut vehicle = Bulldozer::new(
+        ctx,
+        data.engine_data.clone(),
+        rb,
+        image,
+        archetype.collider.size,
+        archetype.health,
+        Positional::default(),
+        movement,
+        Some(vehicle_behavior(archetype, ai, data.markers.ranch_center())),
+        morale,
+    );
+    vehicle.set_faction(archetype.faction);
+    vehicle.set_home(data.markers.ranch_center());
+    vehicle
+}
+
+fn vehicle_behavior(
+    archetype: &EnemyArchetype,
+    ai: DozerBehaviorConfig,
+    home: Point2,
+) -> Box<dyn AiBehavior> {
+    match archetype.tree {
+        Some(ref path) => {
+            let mut tree = BehaviorTree::from_toml(path);
+            let bb = &mut tree.blackboard;
+            bb.home = home;
+            bb.outer_radius = ai.outer_radius;
+            bb.idle_seconds = (
+                ai.idle_min_ms as f32 / 1000.0,
+                ai.idle_max_ms as f32 / 1000.0,
+            );
+            bb.ram_seconds = (ai.ram_min_ms as f32 / 1000.0, ai.ram_max_ms as f32 / 1000.0);
+            Box::new(TreeBehavior::new(tree))
+        }
+        None => Box::new(EnemyDozerBehavior::new(ai, home)),
+    }
+}
+
+fn spawn_pawn(
+    ctx: &mut Context,
+    data: &mut WorldData,
+    archetype: &EnemyArchetype,
+    character: &str,
+    weapon: &str,
+    movement: PawnMovement,
+    pos: Point2,
+) -> Player {
+    let rb = add_enemy_rigid_body(
+        &mut data.world,
+        &archetype.collider,
+        archetype.faction,
+        Vector2::new(1.0, 1.0),
+        pos,
+        0.0,
+    );
+
+    let mut pawn = Player::from_rigid_body(
+        rb,
+        character,
+        archetype.health,
+        Weapon::from_config(ctx, WeaponConfig::from_toml(weapon)),
+        pos,
+        archetype.faction,
+        &data.characters,
+        data.character_spritebatch.clone(),
+    );
+    pawn.set_movement(movement);
+    pawn
+}
+
+pub fn spawn_enemy(
+    ctx: &mut Context,
+    data: &mut WorldData,
+    name: &str,
+    pos: Point2,
+    rotation: f32,
+) -> Box<dyn Enemy> {
+    let archetype = data.archetypes.get(name).clone();
+
+    match (&archetype.body, &archetype.ai) {
+        (BodyConfig::Vehicle { sprite, movement }, AiConfig::Dozer(ai)) => Box::new(spawn_vehicle(
+            ctx,
+            data,
+            &archetype,
+            sprite,
+            *movement,
+            *ai,
+            archetype.morale,
+            pos,
+            rotation,
+        )),
+        (BodyConfig::Vehicle { sprite, movement }, AiConfig::Boss(ai)) => {
+            let vehicle = spawn_vehicle(
+                ctx,
+                data,
+                &archetype,
+                sprite,
+                *movement,
+                ai.ram,
+                // Bosses fight to the end
+                MoraleConfig::fearless(),
+                pos,
+                rotation,
+            );
+            Box::new(Boss::new(vehicle, ai.clone()))
+        }
+        (
+            BodyConfig::Pawn {
+                character,
+                weapon,
+                movement,
+            },
+            AiConfig::Swat(ai),
+        ) => {
+            let pawn = spawn_pawn(ctx, data, &archetype, character, weapon, *movement, pos);
+
+            // The map's patrol ring, when present, overrides the archetype's radii
+            let (ai, patrol_center) = match data.markers.swat_patrol {
+                Some(ref ring) => (
+                    SwatBehaviorConfig {
+                        inner_radius: ring.inner_radius,
+                        outer_radius: ring.outer_radius,
+                        ..*ai
+                    },
+                    ring.center,
+                ),
+                None => (*ai, data.markers.ranch_center()),
+            };
+
+            let tree = BehaviorTree::from_toml(
+                archetype
+                    .tree
+                    .as_ref()
+                    .map_or(SWAT_BEHAVIOR_TREE, String::as_str),
+            );
+
+            Box::new(Swat::new(pawn, ai, tree, patrol_center, archetype.morale))
+        }
+        (
+            BodyConfig::Pawn {
+                character,
+                weapon,
+                movement,
+            },
+            AiConfig::K9(ai),
+        ) => {
+            let pawn = spawn_pawn(ctx, data, &archetype, character, weapon, *movement, pos);
+            Box::new(K9::new(pawn, *ai))
+        }
+        (
+            BodyConfig::Pawn {
+                character,
+                weapon,
+                movement,
+            },
+            AiConfig::Sniper(ai),
+        ) => {
+            let pawn = spawn_pawn(ctx, data, &archetype, character, weapon, *movement, pos);
+            let perch = pick_perch(data, pos, ai);
+            Box::new(Sniper::new(pawn, *ai, perch))
+        }
+        (BodyConfig::Aircraft {}, AiConfig::Helicopter(ai)) => {
+            let rb = add_enemy_rigid_body(
+                &mut data.world,
+                &archetype.collider,
+                archetype.faction,
+                Vector2::new(1.0, 1.0),
+                pos,
+                rotation,
+            );
+            let mut helicopter =
+                Helicopter::new(rb, *ai, archetype.collider.size, archetype.health, pos);
+            helicopter.set_faction(archetype.faction);
+            Box::new(helicopter)
+        }
+        _ => panic!(
+            "enemy archetype {}: AI behavior does not match its body kind",
+            archetype.name
+        ),
+    }
+}
@@ -0,0 +1,696 @@
+use crate::{clamp_norm, AiBehavior, AiDebug, Movement, Point2, Positional, RigidBody, Vector2};
+
+use rand::Rng;
+use std::io::Read;
+
+const TICK_SECONDS: f32 = 1.0 / 60.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    Home,
+    Player,
+    Waypoint,
+    // The ranch house once the walls are breached, home until then
+    Objective,
+}
+
+// A number written into the tree, or one of the values the host puts on the
+// blackboard, e.g. `distance = "outer_radius"`
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(untagged)]
+pub enum Param {
+    Value(f32),
+    Named(ParamName),
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamName {
+    InnerRadius,
+    OuterRadius,
+    IdleMinSeconds,
+    IdleMaxSeconds,
+    RamMinSeconds,
+    RamMaxSeconds,
+}
+
+// Trees are authored in TOML, one node per table, e.g.
+// `{ node = "wait", min_seconds = 1.0, max_seconds = 2.0 }`
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "node", rename_all = "snake_case")]
+pub enum NodeConfig {
+    // Composites
+    Sequence {
+        children: Vec<NodeConfig>,
+    },
+    ReactiveSequence {
+        children: Vec<NodeConfig>,
+    },
+    Selector {
+        children: Vec<NodeConfig>,
+    },
+
+    // Decorators
+    Inverter {
+        child: Box<NodeConfig>,
+    },
+    // Turns failure into success, for steps a sequence can do without
+    Succeeder {
+        child: Box<NodeConfig>,
+    },
+    Once {
+        child: Box<NodeConfig>,
+    },
+    Cooldown {
+        seconds: f32,
+        child: Box<NodeConfig>,
+    },
+    TimeLimit {
+        min_seconds: Param,
+        max_seconds: Param,
+        child: Box<NodeConfig>,
+    },
+    Until {
+        condition: Box<NodeConfig>,
+        child: Box<NodeConfig>,
+    },
+
+    // Conditions
+    CloserThan {
+        target: Target,
+        distance: Param,
+    },
+    FartherThan {
+        target: Target,
+        distance: Param,
+    },
+    Stalled {
+        ratio: f32,
+    },
+    PlayerVisible,
+    Routed,
+    HasObjective,
+
+    // Actions
+    Wait {
+        min_seconds: Param,
+        max_seconds: Param,
+    },
+    Drive {
+        forward: f32,
+        steer_at: Target,
+    },
+    Walk {
+        to: Target,
+        speed: f32,
+    },
+    AimAt {
+        target: Target,
+    },
+    Shoot,
+    PickPatrolPoint,
+    PickFleePoint,
+}
+
+// What the tree knows about its agent and the world. The host fills in the inputs
+// before every tick and reads the outputs back afterwards.
+pub struct Blackboard {
+    pub time: f32,
+    pub positional: Positional,
+    pub speed: f32,
+    pub last_speed: f32,
+    pub home: Point2,
+    pub player_pos: Point2,
+    pub player_visible: bool,
+    pub routed: bool,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub idle_seconds: (f32, f32),
+    pub ram_seconds: (f32, f32),
+    pub move_speed: f32,
+    pub waypoint: Option<Point2>,
+    pub objective: Option<Point2>,
+    walk_direction: f32,
+
+    // Movement and shooting are cleared every tick; the aim sticks
+    pub movement: Movement,
+    pub shoot: bool,
+    pub aim_pos: Option<Point2>,
+
+    // The leaf left running this tick, for debugging
+    active: Option<(&'static str, Option<Point2>)>,
+}
+
+impl Default for Blackboard {
+    fn default() -> Self {
+        Blackboard {
+            time: 0.0,
+            positional: Positional::default(),
+            speed: 0.0,
+            last_speed: 0.0,
+            home: Point2::origin(),
+            player_pos: Point2::origin(),
+            player_visible: false,
+            routed: false,
+            inner_radius: 0.0,
+            outer_radius: 0.0,
+            idle_seconds: (0.0, 0.0),
+            ram_seconds: (0.0, 0.0),
+            move_speed: 1.0,
+            waypoint: None,
+            objective: None,
+            walk_direction: if rand::thread_rng().gen::<bool>() {
+                1.0
+            } else {
+                -1.0
+            },
+            movement: Movement::default(),
+            shoot: false,
+            aim_pos: None,
+            active: None,
+        }
+    }
+}
+
+impl Blackboard {
+    pub fn target_pos(&self, target: Target) -> Option<Point2> {
+        match target {
+            Target::Home => Some(self.home),
+            Target::Player => Some(self.player_pos),
+            Target::Waypoint => self.waypoint,
+            Target::Objective => self.objective.or(Some(self.home)),
+        }
+    }
+
+    pub fn walk_direction(&self) -> f32 {
+        self.walk_direction
+    }
+
+    pub fn param(&self, param: Param) -> f32 {
+        match param {
+            Param::Value(value) => value,
+            Param::Named(ParamName::InnerRadius) => self.inner_radius,
+            Param::Named(ParamName::OuterRadius) => self.outer_radius,
+            Param::Named(ParamName::IdleMinSeconds) => self.idle_seconds.0,
+            Param::Named(ParamName::IdleMaxSeconds) => self.idle_seconds.1,
+            Param::Named(ParamName::RamMinSeconds) => self.ram_seconds.0,
+            Param::Named(ParamName::RamMaxSeconds) => self.ram_seconds.1,
+        }
+    }
+
+    // Drop the waypoint and head the other way around the ring
+    pub fn unstick(&mut self) {
+        self.waypoint = None;
+        self.walk_direction *= -1.0;
+    }
+
+    pub fn distance_to(&self, target: Target) -> Option<f32> {
+        self.target_pos(target)
+            .map(|pos| (pos - self.positional.position).norm())
+    }
+
+    fn random_until(&self, min_seconds: Param, max_seconds: Param) -> f32 {
+        let min_seconds = self.param(min_seconds);
+        let max_seconds = self.param(max_seconds);
+        if max_seconds > min_seconds {
+            self.time + rand::thread_rng().gen_range(min_seconds, max_seconds)
+        } else {
+            self.time + min_seconds
+        }
+    }
+
+    // Somewhere further along the ring between the inner and outer radius
+    fn pick_patrol_point(&mut self) {
+        let pos = self.positional.position - self.home;
+        let center_dist = pos.norm();
+
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() < 0.1 {
+            self.walk_direction *= -1.0;
+        }
+
+        let inner_radius = self.inner_radius;
+        let outer_radius = self.outer_radius;
+
+        let goal = if center_dist < inner_radius || center_dist > outer_radius {
+            self.home + pos.normalize() * (inner_radius * 0.5 + outer_radius * 0.5)
+        } else {
+            let a = pos.y.atan2(pos.x) + (0.1 + 0.1 * rng.gen::<f32>()) * self.walk_direction;
+            self.home
+                + Vector2::new(a.cos(), a.sin())
+                    * (inner_radius + (outer_radius - inner_radius) * rng.gen::<f32>())
+        };
+
+        self.waypoint = Some(goal);
+    }
+
+    // Well past the edge of the map, directly away from home
+    fn pick_flee_point(&mut self) {
+        let pos = self.positional.position - self.home;
+        let away = if pos.norm() > 1e-3 {
+            pos.normalize()
+        } else {
+            Vector2::new(1.0, 0.0)
+        };
+
+        self.waypoint = Some(self.positional.position + away * 100.0);
+    }
+}
+
+enum Node {
+    Sequence {
+        children: Vec<Node>,
+        current: usize,
+    },
+    ReactiveSequence {
+        children: Vec<Node>,
+    },
+    Selector {
+        children: Vec<Node>,
+    },
+    Inverter(Box<Node>),
+    Succeeder(Box<Node>),
+    Once {
+        child: Box<Node>,
+        done: bool,
+    },
+    Cooldown {
+        seconds: f32,
+        child: Box<Node>,
+        ready_at: f32,
+    },
+    TimeLimit {
+        min_seconds: Param,
+        max_seconds: Param,
+        child: Box<Node>,
+        until: Option<f32>,
+    },
+    Until {
+        condition: Box<Node>,
+        child: Box<Node>,
+    },
+    Wait {
+        min_seconds: Param,
+        max_seconds: Param,
+        until: Option<f32>,
+    },
+    Leaf(NodeConfig),
+}
+
+impl Node {
+    fn new(cfg: &NodeConfig) -> Self {
+        let boxed = |child: &NodeConfig| Box::new(Node::new(child));
+
+        match cfg {
+            NodeConfig::Sequence { children } => Node::Sequence {
+                children: children.iter().map(Node::new).collect(),
+                current: 0,
+            },
+            NodeConfig::ReactiveSequence { children } => Node::ReactiveSequence {
+                children: children.iter().map(Node::new).collect(),
+            },
+            NodeConfig::Selector { children } => Node::Selector {
+                children: children.iter().map(Node::new).collect(),
+            },
+            NodeConfig::Inverter { child } => Node::Inverter(boxed(child)),
+            NodeConfig::Succeeder { child } => Node::Succeeder(boxed(child)),
+            NodeConfig::Once { child } => Node::Once {
+                child: boxed(child),
+                done: false,
+            },
+            NodeConfig::Cooldown { seconds, child } => Node::Cooldown {
+                seconds: *seconds,
+                child: boxed(child),
+                ready_at: 0.0,
+            },
+            NodeConfig::TimeLimit {
+                min_seconds,
+                max_seconds,
+                child,
+            } => Node::TimeLimit {
+                min_seconds: *min_seconds,
+                max_seconds: *max_seconds,
+                child: boxed(child),
+                until: None,
+            },
+            NodeConfig::Until { condition, child } => Node::Until {
+                condition: boxed(condition),
+                child: boxed(child),
+            },
+            NodeConfig::Wait {
+                min_seconds,
+                max_seconds,
+            } => Node::Wait {
+                min_seconds: *min_seconds,
+                max_seconds: *max_seconds,
+                until: None,
+            },
+            leaf => Node::Leaf(leaf.clone()),
+        }
+    }
+
+    // Forget any progress, as when a selector switches to another branch.
+    // `Once` and `Cooldown` remember across halts on purpose.
+    fn halt(&mut self) {
+        match self {
+            Node::Sequence { children, current } => {
+                *current = 0;
+                children.iter_mut().for_each(Node::halt);
+            }
+            Node::ReactiveSequence { children } | Node::Selector { children } => {
+                children.iter_mut().for_each(Node::halt)
+            }
+            Node::Inverter(child) | Node::Succeeder(child) => child.halt(),
+            Node::Once { child, .. } | Node::Cooldown { child, .. } => child.halt(),
+            Node::TimeLimit { child, until, .. } => {
+                *until = None;
+                child.halt();
+            }
+            Node::Until { condition, child } => {
+                condition.halt();
+                child.halt();
+            }
+            Node::Wait { until, .. } => *until = None,
+            Node::Leaf(_) => (),
+        }
+    }
+
+    fn tick(&mut self, bb: &mut Blackboard) -> Status {
+        match self {
+            // Resumes from the running child; fails as soon as one child fails.
+            // A failed sequence takes back the shot its earlier children asked for.
+            Node::Sequence { children, current } => {
+                let shoot = bb.shoot;
+                while *current < children.len() {
+                    match children[*current].tick(bb) {
+                        Status::Success => *current += 1,
+                        Status::Running => return Status::Running,
+                        Status::Failure => {
+                            *current = 0;
+                            children.iter_mut().for_each(Node::halt);
+                            bb.shoot = shoot;
+                            return Status::Failure;
+                        }
+                    }
+                }
+                *current = 0;
+                Status::Success
+            }
+            // Re-checks every child each tick, so conditions guard the actions after them
+            Node::ReactiveSequence { children } => {
+                let shoot = bb.shoot;
+                for child in children.iter_mut() {
+                    match child.tick(bb) {
+                        Status::Success => (),
+                        Status::Running => return Status::Running,
+                        Status::Failure => {
+                            children.iter_mut().for_each(Node::halt);
+                            bb.shoot = shoot;
+                            return Status::Failure;
+                        }
+                    }
+                }
+                Status::Success
+            }
+            // Re-evaluated from the top every tick so higher priorities can interrupt
+            Node::Selector { children } => {
+                let mut result = Status::Failure;
+                let mut chosen = children.len();
+                for (i, child) in children.iter_mut().enumerate() {
+                    let status = child.tick(bb);
+                    if status != Status::Failure {
+                        result = status;
+                        chosen = i;
+                        break;
+                    }
+                }
+                for (i, child) in children.iter_mut().enumerate() {
+                    if i != chosen {
+                        child.halt();
+                    }
+                }
+                result
+            }
+            Node::Inverter(child) => match child.tick(bb) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Node::Succeeder(child) => match child.tick(bb) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            Node::Once { child, done } => {
+                if *done {
+                    return Status::Success;
+                }
+                let status = child.tick(bb);
+                if status == Status::Success {
+                    *done = true;
+                }
+                status
+            }
+            Node::Cooldown {
+                seconds,
+                child,
+                ready_at,
+            } => {
+                if bb.time < *ready_at {
+                    return Status::Failure;
+                }
+                let status = child.tick(bb);
+                if status != Status::Running {
+                    *ready_at = bb.time + *seconds;
+                }
+                status
+            }
+            Node::TimeLimit {
+                min_seconds,
+                max_seconds,
+                child,
+                until,
+            } => {
+                let deadline = match *until {
+                    Some(t) => t,
+                    None => {
+                        let t = bb.random_until(*min_seconds, *max_seconds);
+                        *until = Some(t);
+                        t
+                    }
+                };
+
+                let status = child.tick(bb);
+                if status == Status::Running && bb.time < deadline {
+                    return Status::Running;
+                }
+
+                *until = None;
+                child.halt();
+                if status == Status::Running {
+                    Status::Success
+                } else {
+                    status
+                }
+            }
+            Node::Until { condition, child } => {
+                if condition.tick(bb) == Status::Success {
+                    child.halt();
+                    return Status::Success;
+                }
+                match child.tick(bb) {
+                    Status::Failure => Status::Failure,
+                    _ => Status::Running,
+                }
+            }
+            Node::Wait {
+                min_seconds,
+                max_seconds,
+                until,
+            } => {
+                let deadline = match *until {
+                    Some(t) => t,
+                    None => {
+                        let t = bb.random_until(*min_seconds, *max_seconds);
+                        *until = Some(t);
+                        t
+                    }
+                };
+
+                if bb.time >= deadline {
+                    *until = None;
+                    Status::Success
+                } else {
+                    bb.active = Some(("wait", None));
+                    Status::Running
+                }
+            }
+            Node::Leaf(cfg) => tick_leaf(cfg, bb),
+        }
+    }
+}
+
+fn check(condition: bool) -> Status {
+    if condition {
+        Status::Success
+    } else {
+        Status::Failure
+    }
+}
+
+fn tick_leaf(cfg: &NodeConfig, bb: &mut Blackboard) -> Status {
+    match *cfg {
+        NodeConfig::CloserThan { target, distance } => {
+            let distance = bb.param(distance);
+            check(bb.distance_to(target).map_or(false, |d| d < distance))
+        }
+        NodeConfig::FartherThan { target, distance } => {
+            let distance = bb.param(distance);
+            check(bb.distance_to(target).map_or(false, |d| d > distance))
+        }
+        NodeConfig::Stalled { ratio } => check(bb.speed < bb.last_speed * ratio),
+        NodeConfig::PlayerVisible => check(bb.player_visible),
+        NodeConfig::Routed => check(bb.routed),
+        NodeConfig::HasObjective => check(bb.objective.is_some()),
+        NodeConfig::Drive { forward, steer_at } => match bb.target_pos(steer_at) {
+            Some(target) => {
+                bb.movement = Movement {
+                    forward,
+                    right: bb
+                        .positional
+                        .right()
+                        .dot(&(target - bb.positional.position)),
+                };
+                bb.active = Some(("drive", Some(target)));
+                Status::Running
+            }
+            None => Status::Failure,
+        },
+        NodeConfig::Walk { to, speed } => match bb.target_pos(to) {
+            Some(target) => {
+                let offset = target - bb.positional.position;
+                if offset.norm() < 0.5 {
+                    if let Target::Waypoint = to {
+                        bb.waypoint = None;
+                    }
+                    return Status::Success;
+                }
+
+                let offset = clamp_norm(offset, speed * bb.move_speed);
+                bb.movement = Movement {
+                    right: offset.x,
+                    forward: offset.y,
+                };
+                bb.active = Some(("walk", Some(target)));
+                Status::Running
+            }
+            None => Status::Failure,
+        },
+        NodeConfig::AimAt { target } => {
+            bb.aim_pos = bb.target_pos(target);
+            check(bb.aim_pos.is_some())
+        }
+        NodeConfig::Shoot => {
+            bb.shoot = true;
+            Status::Success
+        }
+        NodeConfig::PickPatrolPoint => {
+            bb.pick_patrol_point();
+            Status::Success
+        }
+        NodeConfig::PickFleePoint => {
+            bb.pick_flee_point();
+            Status::Success
+        }
+        _ => unreachable!("composite node ticked as a leaf"),
+    }
+}
+
+pub struct BehaviorTree {
+    root: Node,
+    pub blackboard: Blackboard,
+}
+
+impl BehaviorTree {
+    pub fn new(cfg: &NodeConfig) -> Self {
+        BehaviorTree {
+            root: Node::new(cfg),
+            blackboard: Blackboard::default(),
+        }
+    }
+
+    pub fn from_toml(path: &str) -> Self {
+        let mut file =
+            std::fs::File::open(path).expect(&format!("failed to open behavior tree {}", path));
+        let mut toml = String::new();
+        file.read_to_string(&mut toml)
+            .expect(&format!("failed to open behavior tree {}", path));
+        let cfg: NodeConfig =
+            toml::from_str(&toml).expect(&format!("failed to parse behavior tree {}", path));
+        Self::new(&cfg)
+    }
+
+    pub fn tick(&mut self) -> Status {
+        let bb = &mut self.blackboard;
+        bb.movement = Movement::default();
+        bb.shoot = false;
+        bb.active = None;
+
+        let status = self.root.tick(bb);
+
+        bb.time += TICK_SECONDS;
+        bb.last_speed = bb.speed;
+        status
+    }
+
+    pub fn debug(&self) -> AiDebug {
+        let bb = &self.blackboard;
+        let (state, target) = bb.active.unwrap_or(("idle", None));
+
+        AiDebug {
+            state: state.to_string(),
+            target,
+            path: bb
+                .waypoint
+                .map_or(Vec::new(), |w| vec![bb.positional.position, w]),
+            sight: None,
+        }
+    }
+}
+
+// Drives a vehicle from a tree; only the rigid body is known, so home is the ranch
+pub struct TreeBehavior {
+    tree: BehaviorTree,
+}
+
+impl TreeBehavior {
+    pub fn new(tree: BehaviorTree) -> Self {
+        TreeBehavior { tree }
+    }
+}
+
+impl AiBehavior for TreeBehavior {
+    fn update(&mut self, rb: &RigidBody<f32>) -> Movement {
+        let bb = &mut self.tree.blackboard;
+        bb.positional.set_from_physics(rb);
+        bb.speed = rb.velocity().linear.norm();
+
+        self.tree.tick();
+        self.tree.blackboard.movement
+    }
+
+    fn debug(&self) -> AiDebug {
+        self.tree.debug()
+    }
+
+    fn set_objective(&mut self, objective: Option<Point2>) {
+        self.tree.blackboard.objective = objective;
+    }
+}
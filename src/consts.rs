@@ -2,7 +2,13 @@ pub const COLLIDER_MARGIN: f32 = 0.01;
 pub const DOZER_OUTER_RADIUS: f32 = 40.0;
 pub const SWAT_INNER_RADIUS: f32 = 12.0;
 pub const SWAT_OUTER_RADIUS: f32 = 16.0;
+pub const SWAT_BEHAVIOR_TREE: &str = "resources/behaviors/swat.toml";
 
 pub const GROUP_WORLD: usize = 0;
 pub const GROUP_PLAYER: usize = 1;
 pub const GROUP_ENEMY: usize = 2;
+pub const GROUP_PROJECTILE: usize = 3;
+pub const GROUP_AIR: usize = 4;
+pub const GROUP_PICKUP: usize = 5;
+pub const GROUP_ALLY: usize = 6;
+pub const GROUP_LIVESTOCK: usize = 7;
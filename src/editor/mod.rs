@@ -0,0 +1,612 @@
+use crate::{
+    draw_map_layer, draw_shadowed_text, graphics, map_px_to_world, px_to_world, tile_cell,
+    world_to_map_px, world_to_px, Color, Context, DrawParam, KeyCode, MainState, MapInfo,
+    MapMarkers, Matrix4, MouseButton, Movement, PlayerInput, Point2, Rect, Settings, Vector2,
+    Vector3, WorldData, MAPS_DIR,
+};
+use std::path::Path;
+use tiled::{ObjectShape, PropertyValue};
+
+mod tmx;
+
+use self::tmx::*;
+
+// World units per tick at zoom 1.0
+const PAN_SPEED: f32 = 0.02;
+const MIN_ZOOM: f32 = 0.01;
+const MAX_ZOOM: f32 = 0.3;
+// How close a right click has to be to remove a marker
+const MARKER_PICK_RADIUS: f32 = 1.0;
+
+// Markers a map has at most one of; anything else in the list is an enemy spawn zone
+const SINGLE_MARKERS: [&str; 3] = ["player_start", "intro_player", "intro_sheriff"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum EditMode {
+    Background,
+    Walls,
+    Props,
+    Markers,
+}
+
+impl EditMode {
+    fn next(self) -> Self {
+        match self {
+            EditMode::Background => EditMode::Walls,
+            EditMode::Walls => EditMode::Props,
+            EditMode::Props => EditMode::Markers,
+            EditMode::Markers => EditMode::Background,
+        }
+    }
+
+    fn layer_name(self) -> Option<&'static str> {
+        match self {
+            EditMode::Background => Some("Background"),
+            EditMode::Walls => Some("Walls"),
+            EditMode::Props => Some("Props"),
+            EditMode::Markers => None,
+        }
+    }
+}
+
+pub struct EditorPhase {
+    pub first_update: bool,
+    // Saved and wants a round on the edited map
+    pub test_play: bool,
+    pub exit: bool,
+    mode: EditMode,
+    tile_gids: Vec<u32>,
+    tile_index: usize,
+    marker_kinds: Vec<String>,
+    marker_index: usize,
+    zoom: f32,
+    cursor_px: Point2,
+    // Button held down while painting
+    brush: Option<MouseButton>,
+    notice: String,
+}
+
+impl EditorPhase {
+    pub fn new(_ctx: &mut Context) -> Self {
+        EditorPhase {
+            first_update: true,
+            test_play: false,
+            exit: false,
+            mode: EditMode::Walls,
+            tile_gids: Vec::new(),
+            tile_index: 0,
+            marker_kinds: Vec::new(),
+            marker_index: 0,
+            zoom: 0.05,
+            cursor_px: Point2::origin(),
+            brush: None,
+            notice: String::new(),
+        }
+    }
+
+    pub fn update(&mut self, _settings: &Settings, data: &mut WorldData, ctx: &mut Context) {
+        if self.first_update {
+            data.player_input = PlayerInput::default();
+            data.camera_pos = Point2::origin();
+
+            self.tile_gids = data.map_graphics.tile_gids();
+            self.marker_kinds = SINGLE_MARKERS.iter().map(|&kind| kind.to_owned()).collect();
+            self.marker_kinds.push("ally".to_owned());
+            self.marker_kinds.extend(data.archetypes.names());
+
+            self.first_update = false;
+        }
+
+        let movement = Movement::from(&data.player_input);
+        data.camera_pos += Vector2::new(movement.right, movement.forward) * PAN_SPEED / self.zoom;
+
+        let camera_pos = data.camera_pos;
+        let zoom = self.zoom;
+        self.calculate_view_transform(data, ctx, camera_pos, zoom);
+    }
+
+    pub fn draw(&mut self, _settings: &Settings, data: &mut WorldData, ctx: &mut Context) {
+        let identity_transform = graphics::transform(ctx);
+
+        // Apply our custom transform
+        MainState::apply_view_transform(ctx, data.world_to_screen);
+
+        graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
+
+        for layer_name in &["Background", "Walls", "Props"] {
+            draw_map_layer(
+                ctx,
+                &data.map_graphics,
+                &data.map,
+                layer_name,
+                data.screen_to_world,
+            );
+        }
+
+        self.draw_cursor(data, ctx);
+        let labels = self.draw_markers(data, ctx);
+
+        // Reset to identity transform for text
+        graphics::set_transform(ctx, identity_transform);
+        graphics::apply_transformations(ctx).unwrap();
+
+        for (pos, label) in labels {
+            let text = graphics::Text::new((label, data.font, 24.0));
+            draw_shadowed_text(ctx, pos, &text, Color::from((255, 255, 255, 255)));
+        }
+
+        self.draw_hud(data, ctx);
+    }
+
+    fn draw_cursor(&mut self, data: &mut WorldData, ctx: &mut Context) {
+        let cursor = px_to_world(data.screen_to_world, self.cursor_px.x, self.cursor_px.y);
+        let cell = tile_cell(&data.map);
+
+        let layer_name = match self.mode.layer_name() {
+            Some(layer_name) => layer_name,
+            None => {
+                let mesh = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(0.05),
+                    cursor,
+                    MARKER_PICK_RADIUS,
+                    0.05,
+                    Color::new(1.0, 1.0, 1.0, 0.5),
+                )
+                .unwrap();
+                graphics::draw(ctx, &mesh, DrawParam::new()).unwrap();
+                return;
+            }
+        };
+
+        let (x, y) = match tile_at(data, layer_name, cursor) {
+            Some(tile) => tile,
+            None => return,
+        };
+
+        let center = tile_center(data, layer_name, x, y);
+        let half_cell = cell * 0.5;
+
+        if let Some(&gid) = self.tile_gids.get(self.tile_index) {
+            data.map_graphics.add(
+                &data.map,
+                gid,
+                DrawParam::new()
+                    .dest(center - half_cell)
+                    .color(Color::new(1.0, 1.0, 1.0, 0.6)),
+            );
+            data.map_graphics.draw_and_clear(ctx);
+        }
+
+        let mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(0.05),
+            Rect::new(
+                center.x - half_cell.x,
+                center.y - half_cell.y,
+                cell.x,
+                cell.y,
+            ),
+            Color::new(1.0, 1.0, 0.0, 1.0),
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, DrawParam::new()).unwrap();
+    }
+
+    // Outlines every object on the map; returns the labels to draw in screen space
+    fn draw_markers(&self, data: &WorldData, ctx: &mut Context) -> Vec<(Point2, String)> {
+        let mut labels = Vec::new();
+
+        for group in &data.map.object_groups {
+            for object in &group.objects {
+                let (center, half_extents) = object_extents(&data.map, object);
+                let color = match object.obj_type.as_str() {
+                    "player_start" | "intro_player" => Color::new(0.2, 1.0, 0.2, 1.0),
+                    "intro_sheriff" | "ally" => Color::new(0.3, 0.5, 1.0, 1.0),
+                    "swat_patrol" | "ranch_house" => Color::new(1.0, 1.0, 0.2, 1.0),
+                    _ => Color::new(1.0, 0.3, 0.2, 1.0),
+                };
+
+                let mesh = graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(0.05),
+                    center,
+                    0.3,
+                    0.05,
+                    color,
+                )
+                .unwrap();
+                graphics::draw(ctx, &mesh, DrawParam::new()).unwrap();
+
+                if half_extents.x > 0.0 && half_extents.y > 0.0 {
+                    let mesh = graphics::Mesh::new_rectangle(
+                        ctx,
+                        graphics::DrawMode::stroke(0.05),
+                        Rect::new(
+                            center.x - half_extents.x,
+                            center.y - half_extents.y,
+                            half_extents.x * 2.0,
+                            half_extents.y * 2.0,
+                        ),
+                        color,
+                    )
+                    .unwrap();
+                    graphics::draw(ctx, &mesh, DrawParam::new()).unwrap();
+                }
+
+                labels.push((
+                    world_to_px(data.world_to_screen, center),
+                    object.obj_type.clone(),
+                ));
+            }
+        }
+
+        labels
+    }
+
+    fn draw_hud(&self, data: &WorldData, ctx: &mut Context) {
+        let info = &data.maps[data.map_index];
+        let selection = match self.mode.layer_name() {
+            Some(layer_name) => format!(
+                "Layer: {}   Tile: {}",
+                layer_name,
+                self.tile_gids.get(self.tile_index).cloned().unwrap_or(0)
+            ),
+            None => format!(
+                "Markers: {}",
+                self.marker_kinds
+                    .get(self.marker_index)
+                    .map_or("", String::as_str)
+            ),
+        };
+
+        let lines = [
+            (format!("Editing {}", info.name), 40.0),
+            (selection, 32.0),
+            (
+                "LMB paint / place   RMB erase / remove   [ ] pick tile   Q eyedropper   Tab layer"
+                    .to_owned(),
+                24.0,
+            ),
+            (
+                "WASD pan   Z / X zoom   F2 save   F5 save and play   Esc back to menu".to_owned(),
+                24.0,
+            ),
+            (self.notice.clone(), 24.0),
+        ];
+
+        let mut y = 16.0;
+        for (line, size) in lines.iter() {
+            let text = graphics::Text::new((line.as_str(), data.font, *size));
+            draw_shadowed_text(
+                ctx,
+                Point2::new(16.0, y),
+                &text,
+                Color::from((255, 255, 255, 255)),
+            );
+            y += text.height(ctx) as f32 + 4.0;
+        }
+    }
+
+    pub fn handle_key(
+        &mut self,
+        _settings: &Settings,
+        data: &mut WorldData,
+        _ctx: &mut Context,
+        key_code: KeyCode,
+        value: bool,
+    ) {
+        match key_code {
+            KeyCode::W | KeyCode::Up => data.player_input.up = value,
+            KeyCode::A | KeyCode::Left => data.player_input.left = value,
+            KeyCode::S | KeyCode::Down => data.player_input.down = value,
+            KeyCode::D | KeyCode::Right => data.player_input.right = value,
+            _ if !value => (),
+            KeyCode::Tab => self.mode = self.mode.next(),
+            KeyCode::LBracket => self.select(-1),
+            KeyCode::RBracket => self.select(1),
+            KeyCode::PageUp => self.select(-10),
+            KeyCode::PageDown => self.select(10),
+            KeyCode::Q => self.pick(data),
+            KeyCode::Z => self.zoom = (self.zoom * 0.8).max(MIN_ZOOM),
+            KeyCode::X => self.zoom = (self.zoom * 1.25).min(MAX_ZOOM),
+            KeyCode::F2 => {
+                self.save(data);
+            }
+            KeyCode::F5 => {
+                // Stay in the editor if the map couldn't be saved, so no edits are lost
+                self.test_play = self.save(data);
+            }
+            KeyCode::Escape => self.exit = true,
+            _ => (),
+        }
+    }
+
+    fn select(&mut self, step: i32) {
+        let (index, count) = match self.mode {
+            EditMode::Markers => (&mut self.marker_index, self.marker_kinds.len()),
+            _ => (&mut self.tile_index, self.tile_gids.len()),
+        };
+
+        if count > 0 {
+            let count = count as i32;
+            *index = ((*index as i32 + step) % count + count) as usize % count as usize;
+        }
+    }
+
+    // Eyedropper: selects the tile under the cursor on the current layer
+    fn pick(&mut self, data: &WorldData) {
+        let layer_name = match self.mode.layer_name() {
+            Some(layer_name) => layer_name,
+            None => return,
+        };
+
+        let cursor = px_to_world(data.screen_to_world, self.cursor_px.x, self.cursor_px.y);
+        let layer = data.map.layers.iter().find(|l| l.name == layer_name);
+        if let (Some(layer), Some((x, y))) = (layer, tile_at(data, layer_name, cursor)) {
+            let gid = layer.tiles[(data.map.height - 1 - y) as usize][x as usize];
+            if let Some(index) = self.tile_gids.iter().position(|&g| g == gid) {
+                self.tile_index = index;
+            }
+        }
+    }
+
+    fn apply_brush(&mut self, data: &mut WorldData, button: MouseButton) {
+        let cursor = px_to_world(data.screen_to_world, self.cursor_px.x, self.cursor_px.y);
+
+        match (self.mode.layer_name(), button) {
+            (Some(layer_name), MouseButton::Left) => {
+                if let Some(&gid) = self.tile_gids.get(self.tile_index) {
+                    self.paint(data, layer_name, cursor, gid);
+                }
+            }
+            (Some(layer_name), MouseButton::Right) => self.paint(data, layer_name, cursor, 0),
+            (None, MouseButton::Left) => self.place_marker(data, cursor),
+            (None, MouseButton::Right) => self.remove_marker(data, cursor),
+            _ => (),
+        }
+    }
+
+    fn paint(&mut self, data: &mut WorldData, layer_name: &str, pos: Point2, gid: u32) {
+        let (x, y) = match tile_at(data, layer_name, pos) {
+            Some(tile) => tile,
+            None => return,
+        };
+
+        let row = (data.map.height - 1 - y) as usize;
+        let layer = match data.map.layers.iter_mut().find(|l| l.name == layer_name) {
+            Some(layer) => layer,
+            None => {
+                self.notice = format!("This map has no {} layer", layer_name);
+                return;
+            }
+        };
+
+        if layer.tiles[row][x as usize] != gid {
+            layer.tiles[row][x as usize] = gid;
+            data.map_graphics.rebake_tile(&data.map, layer_name, x, y);
+        }
+    }
+
+    fn place_marker(&mut self, data: &mut WorldData, pos: Point2) {
+        let kind = match self.marker_kinds.get(self.marker_index) {
+            Some(kind) => kind.clone(),
+            None => return,
+        };
+
+        if data.map.object_groups.is_empty() {
+            self.notice = "This map has no object layer for markers".to_owned();
+            return;
+        }
+
+        if SINGLE_MARKERS.contains(&kind.as_str()) {
+            for group in data.map.object_groups.iter_mut() {
+                group.objects.retain(|object| object.obj_type != kind);
+            }
+        }
+
+        let id = data
+            .map
+            .object_groups
+            .iter()
+            .flat_map(|group| group.objects.iter())
+            .map(|object| object.id + 1)
+            .max()
+            .unwrap_or(1);
+        let (x, y) = world_to_map_px(&data.map, pos);
+
+        data.map.object_groups[0].objects.push(tiled::Object {
+            id,
+            gid: 0,
+            name: String::new(),
+            obj_type: kind,
+            width: 0.0,
+            height: 0.0,
+            x,
+            y,
+            rotation: 0.0,
+            visible: true,
+            shape: ObjectShape::Rect {
+                width: 0.0,
+                height: 0.0,
+            },
+            properties: Default::default(),
+        });
+
+        data.markers = MapMarkers::from_map(&data.map);
+    }
+
+    fn remove_marker(&mut self, data: &mut WorldData, pos: Point2) {
+        let mut nearest: Option<(usize, usize, f32)> = None;
+
+        for (g, group) in data.map.object_groups.iter().enumerate() {
+            for (o, object) in group.objects.iter().enumerate() {
+                let (center, _) = object_extents(&data.map, object);
+                let distance = (center - pos).norm();
+                if distance < MARKER_PICK_RADIUS && nearest.map_or(true, |(_, _, d)| distance < d) {
+                    nearest = Some((g, o, distance));
+                }
+            }
+        }
+
+        if let Some((g, o, _)) = nearest {
+            data.map.object_groups[g].objects.remove(o);
+            data.markers = MapMarkers::from_map(&data.map);
+        }
+    }
+
+    // Hand-made maps are overwritten; an edited random ranch is kept as a new map.
+    // Failures are shown in the HUD, and the edits stay in place for another try.
+    fn save(&mut self, data: &mut WorldData) -> bool {
+        let info = data.maps[data.map_index].clone();
+        let path = if info.generated {
+            let seed = match data.map.properties.get("seed") {
+                Some(PropertyValue::StringValue(seed)) => seed.clone(),
+                _ => "edited".to_owned(),
+            };
+
+            data.map.properties.insert(
+                "name".to_owned(),
+                PropertyValue::StringValue(format!("Ranch {}", seed)),
+            );
+
+            Path::new(MAPS_DIR).join(format!("ranch_{}.tmx", seed))
+        } else {
+            info.path.clone()
+        };
+
+        let written = write_tmx(&data.map, &info.path).and_then(|xml| {
+            std::fs::write(&path, xml)
+                .map_err(|err| format!("failed to write map {}: {}", path.display(), err))
+        });
+        if let Err(err) = written {
+            self.notice = format!("Not saved, {}", err);
+            return false;
+        }
+
+        if info.generated {
+            data.map_index = match data.maps.iter().position(|map| map.path == path) {
+                Some(index) => index,
+                None => {
+                    data.maps.push(MapInfo::from_map(&path, &data.map));
+                    data.maps.len() - 1
+                }
+            };
+        }
+
+        self.notice = format!("Saved {}", path.display());
+        true
+    }
+
+    pub fn mouse_motion_event(
+        &mut self,
+        data: &mut WorldData,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        _xrel: f32,
+        _yrel: f32,
+    ) {
+        self.cursor_px = Point2::new(x, y);
+
+        // Markers are placed one click at a time
+        if let Some(button) = self.brush {
+            if self.mode != EditMode::Markers {
+                self.apply_brush(data, button);
+            }
+        }
+    }
+
+    pub fn mouse_button_down_event(
+        &mut self,
+        data: &mut WorldData,
+        _ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) {
+        self.cursor_px = Point2::new(x, y);
+        self.brush = Some(button);
+        self.apply_brush(data, button);
+    }
+
+    pub fn mouse_button_up_event(
+        &mut self,
+        _data: &mut WorldData,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        self.brush = None;
+    }
+
+    pub fn calculate_view_transform(
+        &mut self,
+        data: &mut WorldData,
+        ctx: &Context,
+        origin: Point2,
+        scale: f32,
+    ) {
+        let window_size = graphics::drawable_size(ctx);
+        let viewport_transform = Matrix4::new_translation(&Vector3::new(
+            window_size.0 as f32 * 0.5,
+            window_size.1 as f32 * 0.5,
+            0.0,
+        )) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+            window_size.1 as f32 * 0.5,
+            window_size.1 as f32 * 0.5,
+            1.0,
+        ));
+
+        data.world_to_screen = viewport_transform
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(scale, -scale, 1.0))
+            * Matrix4::new_translation(&Vector3::new(-origin.x, -origin.y, 0.0));
+        data.screen_to_world = data.world_to_screen.try_inverse().unwrap();
+    }
+}
+
+// Tile under a world position, counting rows from the bottom like `TileMapLayerView`
+fn tile_at(data: &WorldData, layer_name: &str, pos: Point2) -> Option<(u32, u32)> {
+    let map = &data.map;
+    let cell = tile_cell(map);
+    let offset = data.map_graphics.layer_offset(map, layer_name);
+
+    let x = ((pos.x - offset.x) / cell.x + map.width as f32 * 0.5).round();
+    let y = ((pos.y - offset.y) / cell.y + map.height as f32 * 0.5).round();
+
+    if x < 0.0 || y < 0.0 || x >= map.width as f32 || y >= map.height as f32 {
+        None
+    } else {
+        Some((x as u32, y as u32))
+    }
+}
+
+fn tile_center(data: &WorldData, layer_name: &str, x: u32, y: u32) -> Point2 {
+    let map = &data.map;
+    let cell = tile_cell(map);
+    let offset = data.map_graphics.layer_offset(map, layer_name);
+
+    Point2::new(
+        (x as f32 - map.width as f32 * 0.5) * cell.x,
+        (y as f32 - map.height as f32 * 0.5) * cell.y,
+    ) + offset
+}
+
+// Center and half extents in world units, matching `MapMarkers::from_map`
+fn object_extents(map: &tiled::Map, object: &tiled::Object) -> (Point2, Vector2) {
+    let (width, height) = match object.shape {
+        ObjectShape::Rect { width, height } | ObjectShape::Ellipse { width, height } => {
+            (width, height)
+        }
+        _ => (0.0, 0.0),
+    };
+
+    (
+        map_px_to_world(map, object.x + width * 0.5, object.y + height * 0.5),
+        Vector2::new(
+            width * 0.5 / map.tile_width as f32,
+            height * 0.5 / map.tile_width as f32,
+        ),
+    )
+}
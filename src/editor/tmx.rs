@@ -0,0 +1,267 @@
+use crate::{read_layer_offsets, Vector2};
+use std::fmt::Write;
+use std::path::Path;
+use tiled::{ObjectShape, Properties, PropertyValue};
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::{EmitterConfig, EventWriter};
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_properties(out: &mut String, properties: &Properties, indent: &str) {
+    if properties.is_empty() {
+        return;
+    }
+
+    // Sorted so saving twice gives the same file
+    let mut names: Vec<&String> = properties.keys().collect();
+    names.sort();
+
+    writeln!(out, "{}<properties>", indent).unwrap();
+    for name in names {
+        let (kind, value) = match properties[name] {
+            PropertyValue::StringValue(ref value) => ("", escape(value)),
+            PropertyValue::IntValue(value) => ("int", value.to_string()),
+            PropertyValue::FloatValue(value) => ("float", value.to_string()),
+            PropertyValue::BoolValue(value) => ("bool", value.to_string()),
+            _ => continue,
+        };
+
+        if kind.is_empty() {
+            writeln!(
+                out,
+                "{} <property name=\"{}\" value=\"{}\"/>",
+                indent,
+                escape(name),
+                value
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                out,
+                "{} <property name=\"{}\" type=\"{}\" value=\"{}\"/>",
+                indent,
+                escape(name),
+                kind,
+                value
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "{}</properties>", indent).unwrap();
+}
+
+// The tiled crate inlines external tilesets when parsing, so the `<tileset>` elements are
+// copied over from the original file
+fn tileset_elements(xml: &str) -> Result<Vec<String>, String> {
+    let mut elements = Vec::new();
+    let mut writer: Option<EventWriter<Vec<u8>>> = None;
+    let mut depth = 0;
+
+    for event in EventReader::from_str(xml) {
+        let event = event.map_err(|err| err.to_string())?;
+
+        if let XmlEvent::StartElement { ref name, .. } = event {
+            depth += 1;
+            if depth == 2 && name.local_name == "tileset" {
+                writer = Some(
+                    EmitterConfig::new()
+                        .write_document_declaration(false)
+                        .create_writer(Vec::new()),
+                );
+            }
+        }
+
+        if let Some(ref mut writer) = writer {
+            if let Some(event) = event.as_writer_event() {
+                writer.write(event).map_err(|err| err.to_string())?;
+            }
+        }
+
+        if let XmlEvent::EndElement { .. } = event {
+            depth -= 1;
+            if depth == 1 {
+                if let Some(writer) = writer.take() {
+                    elements.push(String::from_utf8_lossy(&writer.into_inner()).into_owned());
+                }
+            }
+        }
+    }
+
+    Ok(elements)
+}
+
+fn write_layer(out: &mut String, id: usize, layer: &tiled::Layer, offset: Option<&Vector2>) {
+    let height = layer.tiles.len();
+    let width = layer.tiles.first().map_or(0, |row| row.len());
+
+    write!(
+        out,
+        " <layer id=\"{}\" name=\"{}\" width=\"{}\" height=\"{}\"",
+        id,
+        escape(&layer.name),
+        width,
+        height
+    )
+    .unwrap();
+    if layer.opacity < 1.0 {
+        write!(out, " opacity=\"{}\"", layer.opacity).unwrap();
+    }
+    if !layer.visible {
+        write!(out, " visible=\"0\"").unwrap();
+    }
+    if let Some(offset) = offset {
+        write!(out, " offsetx=\"{}\" offsety=\"{}\"", offset.x, offset.y).unwrap();
+    }
+    writeln!(out, ">").unwrap();
+
+    write_properties(out, &layer.properties, "  ");
+
+    let rows: Vec<String> = layer
+        .tiles
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|gid| gid.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect();
+    writeln!(
+        out,
+        "  <data encoding=\"csv\">\n{}\n</data>",
+        rows.join(",\n")
+    )
+    .unwrap();
+    writeln!(out, " </layer>").unwrap();
+}
+
+fn write_object(out: &mut String, object: &tiled::Object) {
+    write!(out, "  <object id=\"{}\"", object.id).unwrap();
+    if !object.name.is_empty() {
+        write!(out, " name=\"{}\"", escape(&object.name)).unwrap();
+    }
+    if !object.obj_type.is_empty() {
+        write!(out, " type=\"{}\"", escape(&object.obj_type)).unwrap();
+    }
+    if object.gid != 0 {
+        write!(out, " gid=\"{}\"", object.gid).unwrap();
+    }
+    write!(out, " x=\"{}\" y=\"{}\"", object.x, object.y).unwrap();
+
+    let (width, height) = match object.shape {
+        ObjectShape::Rect { width, height } | ObjectShape::Ellipse { width, height } => {
+            (width, height)
+        }
+        _ => (0.0, 0.0),
+    };
+    if width > 0.0 || height > 0.0 {
+        write!(out, " width=\"{}\" height=\"{}\"", width, height).unwrap();
+    }
+    if object.rotation != 0.0 {
+        write!(out, " rotation=\"{}\"", object.rotation).unwrap();
+    }
+    if !object.visible {
+        write!(out, " visible=\"0\"").unwrap();
+    }
+    writeln!(out, ">").unwrap();
+
+    write_properties(out, &object.properties, "   ");
+
+    let points = |points: &[(f32, f32)]| {
+        points
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    match object.shape {
+        ObjectShape::Ellipse { .. } => writeln!(out, "   <ellipse/>").unwrap(),
+        // Zero-sized rectangles are how points come back from the parser
+        ObjectShape::Rect { .. } if width == 0.0 && height == 0.0 => {
+            writeln!(out, "   <point/>").unwrap()
+        }
+        ObjectShape::Polygon { points: ref p } => {
+            writeln!(out, "   <polygon points=\"{}\"/>", points(p)).unwrap()
+        }
+        ObjectShape::Polyline { points: ref p } => {
+            writeln!(out, "   <polyline points=\"{}\"/>", points(p)).unwrap()
+        }
+        _ => (),
+    }
+
+    writeln!(out, "  </object>").unwrap();
+}
+
+// Serializes `map` as Tiled XML. `source` is the file the map was loaded from, which
+// provides the tileset references and layer offsets the parsed map does not keep.
+pub fn write_tmx(map: &tiled::Map, source: &Path) -> Result<String, String> {
+    let xml = std::fs::read_to_string(source)
+        .map_err(|err| format!("failed to open map {}: {}", source.display(), err))?;
+    let layer_offsets = read_layer_offsets(source)?;
+
+    let next_object_id = map
+        .object_groups
+        .iter()
+        .flat_map(|group| group.objects.iter())
+        .map(|object| object.id + 1)
+        .max()
+        .unwrap_or(1);
+
+    let mut out = String::new();
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+    writeln!(
+        out,
+        "<map version=\"1.2\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{}\" height=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" infinite=\"0\" nextlayerid=\"{}\" nextobjectid=\"{}\">",
+        map.width,
+        map.height,
+        map.tile_width,
+        map.tile_height,
+        map.layers.len() + map.object_groups.len() + 1,
+        next_object_id
+    )
+    .unwrap();
+
+    write_properties(&mut out, &map.properties, " ");
+
+    for tileset in tileset_elements(&xml)
+        .map_err(|err| format!("failed to parse map {}: {}", source.display(), err))?
+    {
+        writeln!(out, " {}", tileset).unwrap();
+    }
+
+    for (i, layer) in map.layers.iter().enumerate() {
+        write_layer(&mut out, i + 1, layer, layer_offsets.get(&layer.name));
+    }
+
+    for (i, group) in map.object_groups.iter().enumerate() {
+        write!(
+            out,
+            " <objectgroup id=\"{}\" name=\"{}\"",
+            map.layers.len() + i + 1,
+            escape(&group.name)
+        )
+        .unwrap();
+        if group.opacity < 1.0 {
+            write!(out, " opacity=\"{}\"", group.opacity).unwrap();
+        }
+        if !group.visible {
+            write!(out, " visible=\"0\"").unwrap();
+        }
+        writeln!(out, ">").unwrap();
+
+        for object in &group.objects {
+            write_object(&mut out, object);
+        }
+
+        writeln!(out, " </objectgroup>").unwrap();
+    }
+
+    writeln!(out, "</map>").unwrap();
+    Ok(out)
+}
@@ -1,7 +1,8 @@
 use crate::{
-    clamp_norm, draw_single_image, exponential_distance, AiBehavior, BodyHandle, Bullet, Color,
-    Context, Force2, Movement, PawnInput, Player, Point2, Positional, Settings, Sounds, Vector2,
-    World, GROUP_ENEMY, SWAT_INNER_RADIUS, SWAT_OUTER_RADIUS,
+    draw_single_image, exponential_distance, is_point_visible, AiBehavior, AiDebug, BehaviorTree,
+    BodyHandle, Bullet, Color, Context, DozerBehaviorConfig, Faction, Force2, GrenadeKind,
+    LootKind, Movement, PawnInput, Player, Point2, Positional, Settings, Sounds,
+    StatusEffectConfig, StatusEffects, Vector2, World, SWAT_INNER_RADIUS, SWAT_OUTER_RADIUS,
 };
 
 use super::player::VisualState;
@@ -9,14 +10,288 @@ use super::player::VisualState;
 use ggez::audio;
 use ggez::graphics;
 use nalgebra as na;
-use ncollide2d::query::Ray;
-use ncollide2d::world::CollisionGroups;
+use nphysics2d::algebra::Velocity2;
+use nphysics2d::object::BodyStatus;
 use rand::Rng;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 const SWAT_MOVE_SPEED: f32 = 0.75;
 
+// Damage per second to the ranch house while inside it
+const DOZER_SIEGE_DAMAGE: f32 = 8.0;
+const SWAT_SIEGE_DAMAGE: f32 = 2.0;
+
+// How much of a component's integrity a hit takes, relative to the vehicle's full health
+const COMPONENT_DAMAGE_SCALE: f32 = 2.0;
+const WRECK_BURN_SECONDS: f32 = 30.0;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct VehicleMovement {
+    pub max_torque: f32,
+    pub torque_rate: f32,
+    pub max_spin: f32,
+    pub max_force: f32,
+    pub force_rate: f32,
+    pub max_vel: f32,
+    pub sideways_damping: f32,
+}
+
+impl Default for VehicleMovement {
+    fn default() -> Self {
+        Self {
+            max_torque: 1000.0,
+            torque_rate: 500.0,
+            max_spin: 2.0,
+            max_force: 500.0,
+            force_rate: 200.0,
+            max_vel: 12.0,
+            // Bulldozers technically don't strafe, but we have a need for speed.
+            sideways_damping: 0.1,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct SwatBehaviorConfig {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub move_speed: f32,
+    // Grenades are lobbed at a player hiding in cover within range
+    pub grenades: u32,
+    pub grenade_range: f32,
+    pub grenade_interval_ms: u64,
+}
+
+impl Default for SwatBehaviorConfig {
+    fn default() -> Self {
+        Self {
+            inner_radius: SWAT_INNER_RADIUS,
+            outer_radius: SWAT_OUTER_RADIUS,
+            move_speed: SWAT_MOVE_SPEED,
+            grenades: 0,
+            grenade_range: 20.0,
+            grenade_interval_ms: 8000,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BossConfig {
+    pub title: String,
+    // Flat damage soaked by the armor from every hit outside the weak point
+    pub armor: f32,
+    pub weak_point_multiplier: f32,
+    pub deploy_swat_at: f32,
+    pub swat_archetype: String,
+    pub swat_count: usize,
+    pub tear_gas_at: f32,
+    pub tear_gas_interval_ms: u64,
+    #[serde(default)]
+    pub ram: DozerBehaviorConfig,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct MoraleConfig {
+    // Morale at or below which the enemy breaks and flees the map
+    pub break_at: f32,
+    // Morale lost when taking the enemy's full health in damage
+    pub damage_shock: f32,
+    pub recovery_per_second: f32,
+}
+
+impl Default for MoraleConfig {
+    fn default() -> Self {
+        Self {
+            break_at: 0.3,
+            damage_shock: 0.6,
+            recovery_per_second: 0.02,
+        }
+    }
+}
+
+impl MoraleConfig {
+    // Morale never drops below zero, so this one never breaks
+    pub fn fearless() -> Self {
+        Self {
+            break_at: -1.0,
+            ..Self::default()
+        }
+    }
+}
+
+pub struct Morale {
+    cfg: MoraleConfig,
+    value: f32,
+    routed: bool,
+}
+
+impl Morale {
+    pub fn new(cfg: MoraleConfig) -> Self {
+        Morale {
+            cfg,
+            value: 1.0,
+            routed: false,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn routed(&self) -> bool {
+        self.routed
+    }
+
+    pub fn shake(&mut self, amount: f32) {
+        self.value = (self.value - amount).max(0.0);
+        if self.value <= self.cfg.break_at {
+            self.routed = true;
+        }
+    }
+
+    pub fn on_damage(&mut self, amount: f32, max_health: f32) {
+        self.shake(amount / max_health * self.cfg.damage_shock);
+    }
+
+    // Once broken, an enemy keeps running
+    pub fn update(&mut self, dt: f32) {
+        if !self.routed {
+            self.value = (self.value + self.cfg.recovery_per_second * dt).min(1.0);
+        }
+    }
+}
+
+// Integrity of each vehicle component, from 1 (intact) to 0 (destroyed)
+#[derive(Clone, Copy)]
+pub struct VehicleDamage {
+    pub tracks: f32,
+    pub engine: f32,
+    pub blade: f32,
+}
+
+impl Default for VehicleDamage {
+    fn default() -> Self {
+        Self {
+            tracks: 1.0,
+            engine: 1.0,
+            blade: 1.0,
+        }
+    }
+}
+
+impl VehicleDamage {
+    // Damaged tracks slow the vehicle down and a damaged blade takes the punch out of ramming
+    fn apply(&self, limits: &VehicleMovement) -> VehicleMovement {
+        let tracks = 0.3 + 0.7 * self.tracks;
+        let engine = 0.6 + 0.4 * self.engine;
+        let blade = 0.4 + 0.6 * self.blade;
+
+        VehicleMovement {
+            max_spin: limits.max_spin * tracks,
+            max_vel: limits.max_vel * tracks * engine,
+            max_force: limits.max_force * blade,
+            ..*limits
+        }
+    }
+}
+
+// Draws a column of puffs trailing away from `pos`; `t` keeps them drifting
+fn draw_smoke(ctx: &mut Context, pos: Point2, drift: Vector2, t: f32, amount: f32, color: Color) {
+    for i in 0..4 {
+        let phase = (t * 0.8 + i as f32 * 0.25).fract();
+        let puff_pos = pos + drift * (phase * 3.0) + Vector2::new(0.0, phase * 1.5);
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            puff_pos,
+            0.3 + phase * 0.9,
+            0.05,
+            Color::new(color.r, color.g, color.b, color.a * amount * (1.0 - phase)),
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    }
+}
+
+pub struct Wreck {
+    pub rigid_body: BodyHandle,
+    image: Rc<graphics::Image>,
+    size: f32,
+    positional: Positional,
+    pub life_seconds: f32,
+}
+
+impl Wreck {
+    // Pins the hulk in place so it stays behind as an obstacle
+    pub fn settle(&self, world: &mut World<f32>) {
+        if let Some(rb) = world.rigid_body_mut(self.rigid_body) {
+            rb.set_velocity(Velocity2::zero());
+            rb.set_status(BodyStatus::Static);
+        }
+    }
+
+    // Distance from the center past which nothing touches the hulk, whichever way it faces
+    pub fn clearance(&self) -> f32 {
+        let width = self.image.width() as f32;
+        let height = self.image.height() as f32;
+        self.size * 0.5 * (width * width + height * height).sqrt() / width.min(height)
+    }
+
+    pub fn draw(&self, ctx: &mut Context) {
+        draw_single_image(
+            ctx,
+            &self.image,
+            Color::new(0.2, 0.18, 0.16, 1.0),
+            self.positional.position,
+            self.size,
+            self.positional.rotation,
+        );
+
+        // Burn down over the last third of the wreck's life
+        let t = WRECK_BURN_SECONDS - self.life_seconds;
+        let amount = (self.life_seconds / (WRECK_BURN_SECONDS / 3.0)).min(1.0);
+        let flicker = 0.8 + 0.2 * (t * 23.0).sin();
+
+        draw_smoke(
+            ctx,
+            self.positional.position,
+            Vector2::new(0.5, 0.0),
+            t,
+            amount,
+            Color::new(0.15, 0.15, 0.15, 0.6),
+        );
+        draw_smoke(
+            ctx,
+            self.positional.position,
+            Vector2::zeros(),
+            t * 2.0,
+            amount * flicker,
+            Color::new(1.0, 0.45, 0.1, 0.8),
+        );
+    }
+}
+
+// Things an enemy wants the round to do on its behalf
+pub enum EnemyRequest {
+    Reinforce {
+        archetype: String,
+        count: usize,
+        position: Point2,
+    },
+    TearGas {
+        position: Point2,
+    },
+    Grenade {
+        kind: GrenadeKind,
+        from: Point2,
+        target: Point2,
+    },
+}
+
 pub trait Enemy {
     fn update(
         &mut self,
@@ -34,9 +309,52 @@ pub trait Enemy {
     }
     fn health(&self) -> f32;
     fn damage(&mut self, amount: f32);
+    fn damage_at(&mut self, amount: f32, _hit_pos: Point2) {
+        self.damage(amount);
+    }
     fn alive(&self) -> bool;
     fn closest_target(&self) -> Option<Point2>;
     fn positional(&self) -> Positional;
+    fn drain_requests(&mut self) -> Vec<EnemyRequest> {
+        Vec::new()
+    }
+    fn boss_bar(&self) -> Option<(&str, f32)> {
+        None
+    }
+    // What stays behind in the world once the enemy is destroyed
+    fn wreck(&self) -> Option<Wreck> {
+        None
+    }
+    fn morale(&self) -> f32 {
+        1.0
+    }
+    fn shake_morale(&mut self, _amount: f32) {}
+    fn routed(&self) -> bool {
+        false
+    }
+    // Area lit up for the other units, and whether the player is caught in one
+    fn spotlight(&self) -> Option<(Point2, f32)> {
+        None
+    }
+    fn set_player_spotted(&mut self, _spotted: bool) {}
+    fn apply_status(&mut self, _effect: &StatusEffectConfig) {}
+    // Who the enemy fights for, deciding what it targets and what its bullets can hurt
+    fn faction(&self) -> Faction {
+        Faction::Police
+    }
+    fn ai_debug(&self) -> AiDebug {
+        AiDebug::default()
+    }
+    // Where to head once the walls are breached
+    fn set_objective(&mut self, _objective: Option<Point2>) {}
+    // Damage per second dealt to the ranch house while inside it
+    fn siege_damage(&self) -> f32 {
+        0.0
+    }
+    // Pickups left behind when killed
+    fn drop_loot(&self) -> Vec<LootKind> {
+        Vec::new()
+    }
 }
 
 pub struct Bulldozer {
@@ -44,10 +362,21 @@ pub struct Bulldozer {
     movement: Movement,
     rigid_body: BodyHandle,
     image: Rc<graphics::Image>,
+    size: f32,
     health: f32,
+    max_health: f32,
+    damage: VehicleDamage,
+    morale: Morale,
     positional: Positional,
+    limits: VehicleMovement,
     behavior: Option<Box<dyn AiBehavior>>,
     time_since_last_damage: f32,
+    time_alive: f32,
+    sputter_seconds: f32,
+    status: StatusEffects,
+    faction: Faction,
+    // Where the ranch is, to retreat away from
+    home: Point2,
 }
 
 impl Bulldozer {
@@ -56,8 +385,12 @@ impl Bulldozer {
         engine_sound: audio::SoundData,
         rigid_body: BodyHandle,
         image: Rc<graphics::Image>,
+        size: f32,
+        health: f32,
         positional: Positional,
+        limits: VehicleMovement,
         behavior: Option<Box<dyn AiBehavior>>,
+        morale: MoraleConfig,
     ) -> Self {
         let mut engine_source = audio::SpatialSource::from_data(ctx, engine_sound.clone()).unwrap();
         engine_source.set_repeat(true);
@@ -69,13 +402,87 @@ impl Bulldozer {
             },
             rigid_body,
             image,
-            health: 1.0,
+            size,
+            health,
+            max_health: health,
+            damage: VehicleDamage::default(),
+            morale: Morale::new(morale),
             positional,
+            limits,
             behavior,
             time_since_last_damage: 10000.0,
+            time_alive: 0.0,
+            sputter_seconds: 0.0,
+            status: StatusEffects::default(),
+            faction: Faction::Police,
+            home: Point2::origin(),
+        }
+    }
+
+    pub fn set_faction(&mut self, faction: Faction) {
+        self.faction = faction;
+    }
+
+    pub fn set_home(&mut self, home: Point2) {
+        self.home = home;
+    }
+
+    // Hits on the front take out the blade, hits on the sides the tracks, the rest the engine
+    fn damage_component(&mut self, amount: f32, hit_pos: Point2) {
+        let rel = hit_pos - self.positional.position;
+        let along = self.positional.forward().dot(&rel);
+        let across = self.positional.right().dot(&rel);
+
+        let wear = amount / self.max_health * COMPONENT_DAMAGE_SCALE;
+        let component = if along > 0.3 * self.size {
+            &mut self.damage.blade
+        } else if across.abs() > 0.3 * self.size {
+            &mut self.damage.tracks
+        } else {
+            &mut self.damage.engine
+        };
+
+        *component = (*component - wear).max(0.0);
+    }
+
+    // Back away from the ranch until reaching the map edge
+    fn retreat_movement(&self) -> Movement {
+        let away = self.positional.position - self.home;
+        let away = if away.norm() > 1e-3 {
+            away.normalize()
+        } else {
+            -self.positional.forward()
+        };
+
+        let back = -self.positional.forward();
+        let angle = (back.x * away.y - back.y * away.x).atan2(back.dot(&away));
+
+        Movement {
+            forward: -1.0,
+            right: (-angle * 2.0).min(1.0).max(-1.0),
         }
     }
 
+    fn update_engine_sound(&mut self, volume: f32) {
+        let dt = 1.0 / 60.0;
+        let engine = self.damage.engine.min(self.health / self.max_health);
+
+        // A failing engine coughs and cuts out now and then
+        if self.sputter_seconds > 0.0 {
+            self.sputter_seconds -= dt;
+        } else if engine < 0.6 && rand::thread_rng().gen::<f32>() < (0.6 - engine) * 0.1 {
+            self.sputter_seconds = rand::thread_rng().gen_range(0.1, 0.4);
+        }
+
+        let volume = if self.sputter_seconds > 0.0 {
+            volume * 0.15
+        } else {
+            volume * (0.5 + 0.5 * engine)
+        };
+
+        self.engine_source.set_volume(volume);
+    }
+
     fn apply_physics_movement(&mut self, movement: &Movement, world: &mut World<f32>) {
         let rigid_body = world.rigid_body_mut(self.rigid_body).unwrap();
         let pos = rigid_body.position();
@@ -90,32 +497,27 @@ impl Bulldozer {
         let right_vel = Vector2::dot(&right, &velocity);
 
         let spin = rigid_body.velocity().angular;
-
-        const MAX_TORQUE: f32 = 1000.0;
-        const TORQUE_RATE: f32 = 500.0;
-        const MAX_SPIN: f32 = 2.0;
-
-        const MAX_FORCE: f32 = 500.0;
-        const FORCE_RATE: f32 = 200.0;
-        const MAX_VEL: f32 = 12.0;
-
-        // Bulldozers technically don't strafe, but we have a need for speed.
-        const SIDEWAYS_DAMPING: f32 = 0.1;
+        let limits = &self.damage.apply(&self.limits);
 
         let mut target_vel = movement.forward.min(1.0).max(-1.0);
         let mut target_spin = (-movement.right).min(1.0).max(-1.0);
 
-        target_spin *= MAX_SPIN;
+        target_spin *= limits.max_spin;
         target_spin -= spin;
 
-        target_vel *= MAX_VEL;
+        target_vel *= limits.max_vel;
         target_vel -= fwd_vel;
 
-        let torque = (target_spin * TORQUE_RATE).max(-MAX_TORQUE).min(MAX_TORQUE);
-        let force = forward * (target_vel * FORCE_RATE).max(-MAX_FORCE).min(MAX_FORCE);
+        let torque = (target_spin * limits.torque_rate)
+            .max(-limits.max_torque)
+            .min(limits.max_torque);
+        let force = forward
+            * (target_vel * limits.force_rate)
+                .max(-limits.max_force)
+                .min(limits.max_force);
 
         rigid_body.activate();
-        rigid_body.set_linear_velocity(velocity - right_vel * right * SIDEWAYS_DAMPING);
+        rigid_body.set_linear_velocity(velocity - right_vel * right * limits.sideways_damping);
         rigid_body.apply_force(&Force2::new(force, torque));
     }
 }
@@ -130,11 +532,17 @@ impl Enemy for Bulldozer {
         _bullets_out: &mut Vec<Bullet>,
         _sounds: &mut Sounds,
     ) {
-        if let Some(ref mut behavior) = self.behavior {
+        if self.morale.routed() {
+            self.movement = self.retreat_movement();
+        } else if let Some(ref mut behavior) = self.behavior {
             self.movement = behavior.update(world.rigid_body(self.rigid_body).unwrap());
         }
 
-        self.apply_physics_movement(&movement.unwrap_or(self.movement), world);
+        let mut movement = movement.unwrap_or(self.movement);
+        let speed_scale = self.status.speed_scale();
+        movement.forward *= speed_scale;
+        movement.right *= speed_scale;
+        self.apply_physics_movement(&movement, world);
 
         //self.engine_source.set_ears(na::Point3::new(player_pos.position.x, player_pos.position.y, 1.0), na::Point3::new(player_pos.position.x, player_pos.position.y, 1.0));
         //self.engine_source.set_position(na::Point3::new(self.positional.position.x, self.positional.position.y, 1.0));
@@ -148,14 +556,19 @@ impl Enemy for Bulldozer {
             let volume = exponential_distance(ear_distance, min, max, roll_off);
             //println!("Volume: {}", volume);
 
-            self.engine_source.set_volume(volume);
+            self.update_engine_sound(volume);
 
             if !self.engine_source.playing() {
                 self.engine_source.play().unwrap();
             }
         }
 
+        let status_damage = self.status.update(1.0 / 60.0);
+        self.health = (self.health - status_damage).max(0.0);
+
+        self.morale.update(1.0 / 60.0);
         self.time_since_last_damage += 1.0 / 60.0;
+        self.time_alive += 1.0 / 60.0;
     }
 
     fn rigid_body(&self) -> Option<BodyHandle> {
@@ -168,15 +581,17 @@ impl Enemy for Bulldozer {
             &self.image,
             self.color(),
             self.positional.position,
-            3.0,
+            self.size,
             self.positional.rotation,
         );
+        self.draw_engine_smoke(ctx);
     }
 
     fn color(&self) -> Color {
         let t = self.time_since_last_damage;
         let t = (1.0 - t * 5.0).max(0.0) * 10.0;
-        Color::new(1.0 + t, self.health + t, self.health + t, 1.0)
+        self.status
+            .tint(Color::new(1.0 + t, self.health + t, self.health + t, 1.0))
     }
 
     fn health(&self) -> f32 {
@@ -185,9 +600,15 @@ impl Enemy for Bulldozer {
 
     fn damage(&mut self, amount: f32) {
         self.health = (self.health - amount).max(0.0);
+        self.morale.on_damage(amount, self.max_health);
         self.time_since_last_damage = 0.0;
     }
 
+    fn damage_at(&mut self, amount: f32, hit_pos: Point2) {
+        self.damage_component(amount, hit_pos);
+        self.damage(amount);
+    }
+
     fn alive(&self) -> bool {
         self.health > 0.0
     }
@@ -196,89 +617,170 @@ impl Enemy for Bulldozer {
         None
     }
 
+    fn apply_status(&mut self, effect: &StatusEffectConfig) {
+        self.status.apply(effect);
+    }
+
+    fn faction(&self) -> Faction {
+        self.faction
+    }
     fn positional(&self) -> Positional {
         self.positional.clone()
     }
+
+    fn wreck(&self) -> Option<Wreck> {
+        Some(Wreck {
+            rigid_body: self.rigid_body,
+            image: self.image.clone(),
+            size: self.size,
+            positional: self.positional,
+            life_seconds: WRECK_BURN_SECONDS,
+        })
+    }
+
+    fn ai_debug(&self) -> AiDebug {
+        if self.morale.routed() {
+            AiDebug {
+                state: "retreating".to_string(),
+                ..AiDebug::default()
+            }
+        } else {
+            self.behavior
+                .as_ref()
+                .map_or(AiDebug::default(), |behavior| behavior.debug())
+        }
+    }
+
+    fn set_objective(&mut self, objective: Option<Point2>) {
+        if let Some(ref mut behavior) = self.behavior {
+            behavior.set_objective(objective);
+        }
+    }
+
+    fn siege_damage(&self) -> f32 {
+        DOZER_SIEGE_DAMAGE
+    }
+
+    fn drop_loot(&self) -> Vec<LootKind> {
+        vec![LootKind::Scrap]
+    }
+
+    fn morale(&self) -> f32 {
+        self.morale.value()
+    }
+
+    fn shake_morale(&mut self, amount: f32) {
+        self.morale.shake(amount);
+    }
+
+    fn routed(&self) -> bool {
+        self.morale.routed()
+    }
+}
+
+impl Bulldozer {
+    fn draw_engine_smoke(&self, ctx: &mut Context) {
+        if self.damage.engine < 0.75 {
+            let exhaust = self.positional.position - self.positional.forward() * (self.size * 0.4);
+            draw_smoke(
+                ctx,
+                exhaust,
+                -self.positional.forward(),
+                self.time_alive,
+                1.0 - self.damage.engine,
+                Color::new(0.2, 0.2, 0.2, 0.6),
+            );
+        }
+    }
 }
 
 pub struct Swat {
     pawn: Player,
-    waypoint: Option<Point2>,
-    walk_direction: f32,
-    keep_direction_until: Instant,
+    cfg: SwatBehaviorConfig,
+    tree: BehaviorTree,
+    max_health: f32,
+    morale: Morale,
     next_taunt_at: Instant,
+    grenades: u32,
+    next_grenade_at: Instant,
+    player_spotted: bool,
+    requests: Vec<EnemyRequest>,
+    sight: Option<(Point2, Point2, bool)>,
     avg_velocity: Vector2,
     avg_velocity_samples: u32,
 }
 
 impl Swat {
-    pub fn new(pawn: Player) -> Self {
+    pub fn new(
+        pawn: Player,
+        cfg: SwatBehaviorConfig,
+        mut tree: BehaviorTree,
+        patrol_center: Point2,
+        morale: MoraleConfig,
+    ) -> Self {
         let mut rng = rand::thread_rng();
 
+        tree.blackboard.home = patrol_center;
+        tree.blackboard.inner_radius = cfg.inner_radius;
+        tree.blackboard.outer_radius = cfg.outer_radius;
+        tree.blackboard.move_speed = cfg.move_speed;
+
         Swat {
+            max_health: pawn.health,
             pawn,
-            waypoint: None,
-            walk_direction: if rng.gen::<bool>() { 1.0 } else { -1.0 },
-            keep_direction_until: Instant::now()
-                + Duration::from_millis(rng.gen_range(4000, 20000)),
+            cfg,
+            tree,
+            morale: Morale::new(morale),
             next_taunt_at: Instant::now() + Duration::from_millis(rng.gen_range(4000, 20000)),
+            grenades: cfg.grenades,
+            next_grenade_at: Instant::now() + Duration::from_millis(cfg.grenade_interval_ms),
+            player_spotted: false,
+            requests: Vec::new(),
+            sight: None,
             avg_velocity: Vector2::zeros(),
             avg_velocity_samples: 0,
         }
     }
+}
 
-    fn acquire_waypoint(&mut self) {
-        let pos = self.positional().position.coords;
-        let center_dist = pos.norm();
+impl Swat {
+    fn try_throw_grenade(&mut self, pos: Point2, player_pos: Point2) {
+        // A spotlit player is easy to find from further away
+        let range = if self.player_spotted {
+            self.cfg.grenade_range * 2.0
+        } else {
+            self.cfg.grenade_range
+        };
 
         let now = Instant::now();
-        if now > self.keep_direction_until {
-            let mut rng = rand::thread_rng();
-            self.walk_direction = if rng.gen::<bool>() { 1.0 } else { -1.0 };
-            self.keep_direction_until =
-                Instant::now() + Duration::from_millis(rng.gen_range(4000, 20000));
+        if self.grenades == 0 || now < self.next_grenade_at || (player_pos - pos).norm() > range {
+            return;
         }
 
-        let goal: Point2 = if center_dist < SWAT_INNER_RADIUS || center_dist > SWAT_OUTER_RADIUS {
-            (pos.normalize() * (SWAT_INNER_RADIUS * 0.5 + SWAT_OUTER_RADIUS * 0.5)).into()
+        self.grenades -= 1;
+        self.next_grenade_at = now + Duration::from_millis(self.cfg.grenade_interval_ms);
+
+        let kind = if rand::thread_rng().gen::<bool>() {
+            GrenadeKind::TearGas
         } else {
-            let a = pos.y.atan2(pos.x) + (0.1 + 0.1 * rand::random::<f32>()) * self.walk_direction;
-            (Vector2::new(a.cos(), a.sin())
-                * (SWAT_INNER_RADIUS
-                    + (SWAT_OUTER_RADIUS - SWAT_INNER_RADIUS) * rand::random::<f32>()))
-            .into()
+            GrenadeKind::Flashbang
         };
 
-        self.waypoint = Some(goal);
+        self.requests.push(EnemyRequest::Grenade {
+            kind,
+            from: pos,
+            target: player_pos,
+        });
     }
-}
 
-impl Swat {
     fn is_player_visible(&self, player_pos: &Point2, world: &World<f32>) -> bool {
-        let collision_world = world.collision_world();
-        let swat_pos = self.positional().position;
-
-        let mut groups = CollisionGroups::new();
-        groups.set_blacklist(&[GROUP_ENEMY]);
-
-        let offset = player_pos - swat_pos;
-        let offset_len = offset.norm();
-
-        let ray = Ray {
-            origin: swat_pos,
-            dir: offset * (1.0 / offset_len),
-        };
-
-        let min_toi = collision_world
-            .interferences_with_ray(&ray, &groups)
-            .map(|(_, collision)| collision.toi)
-            .min_by(|a, b| a.partial_cmp(b).unwrap());
-
-        if let Some(min_toi) = min_toi {
-            (min_toi - offset_len).abs() < 0.5
-        } else {
-            false
-        }
+        !self.pawn.status.blinded()
+            && is_point_visible(
+                world,
+                self.positional().position,
+                *player_pos,
+                self.pawn.faction().group(),
+            )
     }
 }
 
@@ -292,6 +794,29 @@ impl Enemy for Swat {
         bullets_out: &mut Vec<Bullet>,
         sounds: &mut Sounds,
     ) {
+        self.morale.update(1.0 / 60.0);
+
+        let now = Instant::now();
+        if now > self.next_taunt_at {
+            let mut rng = rand::thread_rng();
+            self.next_taunt_at = Instant::now() + Duration::from_millis(rng.gen_range(4000, 20000));
+            sounds.play_swat();
+        }
+
+        let pos = self.positional().position;
+        let routed = self.morale.routed();
+        let player_visible = !routed && self.is_player_visible(&player_pos.position, world);
+        self.sight = if routed {
+            None
+        } else {
+            Some((pos, player_pos.position, player_visible))
+        };
+
+        // Flush the player out of cover
+        if !player_visible && !routed {
+            self.try_throw_grenade(pos, player_pos.position);
+        }
+
         let cur_vel = world
             .rigid_body(self.pawn.body_handle)
             .unwrap()
@@ -301,53 +826,31 @@ impl Enemy for Swat {
         self.avg_velocity = self.avg_velocity * 0.9 + cur_vel * 0.1;
         self.avg_velocity_samples += 1;
 
-        // Some chaos. Helps getting unstuck and whatnot
-        if self.avg_velocity.norm() < 0.1 && self.avg_velocity_samples > 10 {
-            self.waypoint = None;
-            self.walk_direction *= -1.0;
-            self.avg_velocity = Vector2::zeros();
-            self.avg_velocity_samples = 0;
-        }
+        {
+            let bb = &mut self.tree.blackboard;
 
-        if self.waypoint.is_none() {
-            self.acquire_waypoint();
-        }
+            // Some chaos. Helps getting unstuck and whatnot
+            if self.avg_velocity.norm() < 0.1 && self.avg_velocity_samples > 10 {
+                bb.unstick();
+                self.avg_velocity = Vector2::zeros();
+                self.avg_velocity_samples = 0;
+            }
 
-        let now = Instant::now();
-        if now > self.next_taunt_at {
-            let mut rng = rand::thread_rng();
-            self.next_taunt_at = Instant::now() + Duration::from_millis(rng.gen_range(4000, 20000));
-            sounds.play_swat();
+            bb.positional = self.pawn.positional;
+            bb.speed = cur_vel.norm();
+            bb.player_pos = player_pos.position;
+            bb.player_visible = player_visible;
+            bb.routed = routed;
         }
 
-        if let Some(w) = self.waypoint {
-            let pos = self.positional().position;
-            if (pos - w).norm() < 0.5 {
-                self.waypoint = None;
-            }
+        self.tree.tick();
 
-            let player_visible = self.is_player_visible(&player_pos.position, world);
-
-            let offset = if player_visible {
-                //Vector2::zeros()
-                clamp_norm(w - pos, SWAT_MOVE_SPEED * 0.5)
-            } else {
-                clamp_norm(w - pos, SWAT_MOVE_SPEED)
-            };
-
-            self.pawn.set_input(PawnInput {
-                movement: Movement {
-                    right: offset.x,
-                    forward: offset.y,
-                },
-                shoot: player_visible,
-                aim_pos: if player_visible {
-                    player_pos.position
-                } else {
-                    Point2::origin()
-                },
-            });
-        }
+        let bb = &self.tree.blackboard;
+        self.pawn.set_input(PawnInput {
+            movement: bb.movement,
+            shoot: bb.shoot,
+            aim_pos: bb.aim_pos.unwrap_or_else(Point2::origin),
+        });
 
         self.pawn.set_visual(VisualState::Gun);
         self.pawn.update(world, bullets_out);
@@ -366,7 +869,8 @@ impl Enemy for Swat {
     }
 
     fn damage(&mut self, amount: f32) {
-        self.pawn.damage(amount)
+        self.pawn.damage(amount);
+        self.morale.on_damage(amount, self.max_health);
     }
 
     fn alive(&self) -> bool {
@@ -377,7 +881,263 @@ impl Enemy for Swat {
         None
     }
 
+    fn apply_status(&mut self, effect: &StatusEffectConfig) {
+        self.pawn.status.apply(effect);
+    }
+
+    fn faction(&self) -> Faction {
+        self.pawn.faction()
+    }
     fn positional(&self) -> Positional {
         self.pawn.positional
     }
+
+    fn morale(&self) -> f32 {
+        self.morale.value()
+    }
+
+    fn shake_morale(&mut self, amount: f32) {
+        self.morale.shake(amount);
+    }
+
+    fn routed(&self) -> bool {
+        self.morale.routed()
+    }
+
+    fn drain_requests(&mut self) -> Vec<EnemyRequest> {
+        std::mem::replace(&mut self.requests, Vec::new())
+    }
+
+    fn set_player_spotted(&mut self, spotted: bool) {
+        self.player_spotted = spotted;
+    }
+
+    fn ai_debug(&self) -> AiDebug {
+        let mut debug = self.tree.debug();
+        let direction = if self.tree.blackboard.walk_direction() > 0.0 {
+            "ccw"
+        } else {
+            "cw"
+        };
+        debug.state = format!("{} ({})", debug.state, direction);
+        debug.sight = self.sight;
+        debug
+    }
+
+    fn set_objective(&mut self, objective: Option<Point2>) {
+        self.tree.blackboard.objective = objective;
+    }
+
+    fn siege_damage(&self) -> f32 {
+        SWAT_SIEGE_DAMAGE
+    }
+
+    fn drop_loot(&self) -> Vec<LootKind> {
+        let roll = rand::thread_rng().gen::<f32>();
+        if roll < 0.35 {
+            vec![LootKind::Ammo]
+        } else if roll < 0.55 {
+            vec![LootKind::Medkit]
+        } else if roll < 0.7 {
+            vec![LootKind::Smg]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum BossPhase {
+    Ramming,
+    DeployingUntil(Instant),
+    TearGas,
+}
+
+pub struct Boss {
+    vehicle: Bulldozer,
+    cfg: BossConfig,
+    max_health: f32,
+    phase: BossPhase,
+    swat_deployed: bool,
+    next_tear_gas_at: Instant,
+    requests: Vec<EnemyRequest>,
+}
+
+impl Boss {
+    pub fn new(vehicle: Bulldozer, cfg: BossConfig) -> Self {
+        Boss {
+            max_health: vehicle.health,
+            vehicle,
+            cfg,
+            phase: BossPhase::Ramming,
+            swat_deployed: false,
+            next_tear_gas_at: Instant::now(),
+            requests: Vec::new(),
+        }
+    }
+
+    fn health_fraction(&self) -> f32 {
+        self.vehicle.health / self.max_health
+    }
+
+    // The engine block at the back is the weak point
+    fn is_weak_point(&self, hit_pos: Point2) -> bool {
+        let positional = &self.vehicle.positional;
+        positional.forward().dot(&(hit_pos - positional.position)) < -0.25 * self.vehicle.size
+    }
+}
+
+impl Enemy for Boss {
+    fn update(
+        &mut self,
+        settings: &Settings,
+        player_pos: Positional,
+        movement: Option<Movement>,
+        world: &mut World<f32>,
+        bullets_out: &mut Vec<Bullet>,
+        sounds: &mut Sounds,
+    ) {
+        let now = Instant::now();
+        let fraction = self.health_fraction();
+
+        if !self.swat_deployed && fraction <= self.cfg.deploy_swat_at {
+            self.swat_deployed = true;
+            self.phase = BossPhase::DeployingUntil(now + Duration::from_millis(2000));
+
+            let positional = &self.vehicle.positional;
+            self.requests.push(EnemyRequest::Reinforce {
+                archetype: self.cfg.swat_archetype.clone(),
+                count: self.cfg.swat_count,
+                position: positional.position - positional.forward() * self.vehicle.size,
+            });
+        }
+
+        match self.phase {
+            BossPhase::DeployingUntil(t) => {
+                if now > t {
+                    self.phase = BossPhase::Ramming;
+                }
+            }
+            BossPhase::Ramming => {
+                if fraction <= self.cfg.tear_gas_at {
+                    self.phase = BossPhase::TearGas;
+                    self.next_tear_gas_at = now;
+                }
+            }
+            BossPhase::TearGas => {
+                if now >= self.next_tear_gas_at {
+                    self.next_tear_gas_at =
+                        now + Duration::from_millis(self.cfg.tear_gas_interval_ms);
+                    self.requests.push(EnemyRequest::TearGas {
+                        position: player_pos.position,
+                    });
+                }
+            }
+        }
+
+        // Hold still while the SWAT team piles out
+        let movement = match self.phase {
+            BossPhase::DeployingUntil(_) => Some(Movement::default()),
+            _ => movement,
+        };
+
+        self.vehicle
+            .update(settings, player_pos, movement, world, bullets_out, sounds);
+    }
+
+    fn rigid_body(&self) -> Option<BodyHandle> {
+        self.vehicle.rigid_body()
+    }
+
+    fn draw(&self, ctx: &mut Context) {
+        draw_single_image(
+            ctx,
+            &self.vehicle.image,
+            self.color(),
+            self.vehicle.positional.position,
+            self.vehicle.size,
+            self.vehicle.positional.rotation,
+        );
+        self.vehicle.draw_engine_smoke(ctx);
+    }
+
+    fn color(&self) -> Color {
+        let t = self.vehicle.time_since_last_damage;
+        let t = (1.0 - t * 5.0).max(0.0) * 10.0;
+        let f = self.health_fraction();
+        self.vehicle
+            .status
+            .tint(Color::new(0.5 + t, 0.5 * f + t, 0.6 * f + t, 1.0))
+    }
+
+    fn health(&self) -> f32 {
+        self.vehicle.health()
+    }
+
+    fn damage(&mut self, amount: f32) {
+        self.vehicle.damage((amount - self.cfg.armor).max(0.0));
+    }
+
+    fn damage_at(&mut self, amount: f32, hit_pos: Point2) {
+        let amount = if self.is_weak_point(hit_pos) {
+            amount * self.cfg.weak_point_multiplier
+        } else {
+            (amount - self.cfg.armor).max(0.0)
+        };
+        self.vehicle.damage_at(amount, hit_pos);
+    }
+
+    fn alive(&self) -> bool {
+        self.vehicle.alive()
+    }
+
+    fn closest_target(&self) -> Option<Point2> {
+        None
+    }
+
+    fn apply_status(&mut self, effect: &StatusEffectConfig) {
+        self.vehicle.status.apply(effect);
+    }
+
+    fn faction(&self) -> Faction {
+        self.vehicle.faction
+    }
+    fn positional(&self) -> Positional {
+        self.vehicle.positional()
+    }
+
+    fn drain_requests(&mut self) -> Vec<EnemyRequest> {
+        std::mem::replace(&mut self.requests, Vec::new())
+    }
+
+    fn boss_bar(&self) -> Option<(&str, f32)> {
+        Some((&self.cfg.title, self.health_fraction()))
+    }
+
+    fn wreck(&self) -> Option<Wreck> {
+        self.vehicle.wreck()
+    }
+
+    fn ai_debug(&self) -> AiDebug {
+        let mut debug = self.vehicle.ai_debug();
+        let phase = match self.phase {
+            BossPhase::Ramming => "ramming",
+            BossPhase::DeployingUntil(_) => "deploying",
+            BossPhase::TearGas => "tear gas",
+        };
+        debug.state = format!("{}: {}", phase, debug.state);
+        debug
+    }
+
+    fn set_objective(&mut self, objective: Option<Point2>) {
+        self.vehicle.set_objective(objective);
+    }
+
+    fn siege_damage(&self) -> f32 {
+        self.vehicle.siege_damage()
+    }
+
+    fn drop_loot(&self) -> Vec<LootKind> {
+        vec![LootKind::Scrap; 3]
+    }
 }
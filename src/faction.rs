@@ -0,0 +1,105 @@
+use crate::{GROUP_ALLY, GROUP_ENEMY, GROUP_LIVESTOCK, GROUP_PLAYER};
+
+use ncollide2d::world::CollisionGroups;
+use std::io::Read;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Faction {
+    Player,
+    Allies,
+    Police,
+    Livestock,
+}
+
+impl Default for Faction {
+    fn default() -> Self {
+        Faction::Police
+    }
+}
+
+impl Faction {
+    pub const ALL: [Faction; 4] = [
+        Faction::Player,
+        Faction::Allies,
+        Faction::Police,
+        Faction::Livestock,
+    ];
+
+    // Each faction gets its own collision group so rays can tell them apart
+    pub fn group(self) -> usize {
+        match self {
+            Faction::Player => GROUP_PLAYER,
+            Faction::Allies => GROUP_ALLY,
+            Faction::Police => GROUP_ENEMY,
+            Faction::Livestock => GROUP_LIVESTOCK,
+        }
+    }
+
+    pub fn groups() -> Vec<usize> {
+        Faction::ALL.iter().map(|f| f.group()).collect()
+    }
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FactionRules {
+    // Factions this one attacks on sight, and whose members its bullets hurt
+    pub hostile: Vec<Faction>,
+    // Whether stray bullets also hurt everyone else; the shooter's own faction is always safe
+    pub friendly_fire: bool,
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FactionTable {
+    player: FactionRules,
+    allies: FactionRules,
+    police: FactionRules,
+    livestock: FactionRules,
+}
+
+impl FactionTable {
+    pub fn from_toml(path: &str) -> Self {
+        let mut file =
+            std::fs::File::open(path).expect(&format!("failed to open faction table {}", path));
+        let mut toml = String::new();
+        file.read_to_string(&mut toml)
+            .expect(&format!("failed to open faction table {}", path));
+        toml::from_str(&toml).expect(&format!("failed to parse faction table {}", path))
+    }
+
+    fn rules(&self, faction: Faction) -> &FactionRules {
+        match faction {
+            Faction::Player => &self.player,
+            Faction::Allies => &self.allies,
+            Faction::Police => &self.police,
+            Faction::Livestock => &self.livestock,
+        }
+    }
+
+    pub fn is_hostile(&self, attacker: Faction, target: Faction) -> bool {
+        self.rules(attacker).hostile.contains(&target)
+    }
+
+    pub fn can_damage(&self, shooter: Faction, victim: Faction) -> bool {
+        if shooter == victim {
+            return false;
+        }
+
+        self.is_hostile(shooter, victim) || self.rules(shooter).friendly_fire
+    }
+
+    // Ray groups for a bullet, passing through everyone it can't hurt
+    pub fn bullet_groups(&self, shooter: Faction) -> CollisionGroups {
+        let blacklist: Vec<usize> = Faction::ALL
+            .iter()
+            .filter(|victim| !self.can_damage(shooter, **victim))
+            .map(|victim| victim.group())
+            .collect();
+
+        let mut groups = CollisionGroups::new();
+        groups.set_blacklist(&blacklist);
+        groups
+    }
+}
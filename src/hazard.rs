@@ -0,0 +1,211 @@
+use crate::{
+    graphics, is_point_visible, Ball, BodyHandle, Color, Context, Faction, Isometry2, Material,
+    Point2, ShapeHandle, StatusEffectConfig, Vector2, Volumetric, World, COLLIDER_MARGIN,
+    GROUP_PROJECTILE,
+};
+
+use ncollide2d::world::CollisionGroups;
+use std::io::Read;
+
+const GRENADE_RADIUS: f32 = 0.15;
+const GRENADE_FLIGHT_SECONDS: f32 = 1.0;
+const GRENADE_FUSE_SECONDS: f32 = 2.0;
+const FLASHBANG_RADIUS: f32 = 8.0;
+const FLASHBANG_SECONDS: f32 = 3.0;
+
+#[derive(Deserialize, Clone)]
+pub struct GasCloudConfig {
+    pub radius: f32,
+    pub life_seconds: f32,
+    pub damage_per_second: f32,
+    // Fraction of the player's top speed left while inside the cloud
+    pub slow: f32,
+    // Reapplied every step to anyone inside the cloud
+    #[serde(default)]
+    pub effects: Vec<StatusEffectConfig>,
+}
+
+impl GasCloudConfig {
+    pub fn from_toml(path: &str) -> Self {
+        let mut file =
+            std::fs::File::open(path).expect(&format!("failed to open hazard config {}", path));
+        let mut toml = String::new();
+        file.read_to_string(&mut toml)
+            .expect(&format!("failed to open hazard config {}", path));
+        toml::from_str(&toml).expect(&format!("failed to parse hazard config {}", path))
+    }
+}
+
+pub struct GasCloud {
+    pub position: Point2,
+    pub radius: f32,
+    pub life_seconds: f32,
+    pub damage_per_second: f32,
+    pub slow: f32,
+    pub effects: Vec<StatusEffectConfig>,
+}
+
+impl GasCloud {
+    pub fn new(cfg: &GasCloudConfig, position: Point2) -> Self {
+        GasCloud {
+            position,
+            radius: cfg.radius,
+            life_seconds: cfg.life_seconds,
+            damage_per_second: cfg.damage_per_second,
+            slow: cfg.slow,
+            effects: cfg.effects.clone(),
+        }
+    }
+
+    pub fn contains(&self, pos: Point2) -> bool {
+        (pos - self.position).norm() < self.radius
+    }
+
+    pub fn draw(&self, ctx: &mut Context) {
+        // Fade out over the last couple of seconds
+        let alpha = 0.35 * self.life_seconds.min(2.0) / 2.0;
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            self.position,
+            self.radius,
+            0.05,
+            Color::new(0.8, 0.85, 0.6, alpha),
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GrenadeKind {
+    TearGas,
+    Flashbang,
+}
+
+pub enum Detonation {
+    Gas(GasCloud),
+    // How long the player is blinded for, if caught in the flash
+    Flash(f32),
+}
+
+fn grenade_groups() -> CollisionGroups {
+    let mut groups = CollisionGroups::new();
+    groups.set_membership(&[GROUP_PROJECTILE]);
+    // Grenades fly past pawns and vehicles, but bounce off walls all the way
+    let mut blacklist = Faction::groups();
+    blacklist.push(GROUP_PROJECTILE);
+    groups.set_blacklist(&blacklist);
+    groups
+}
+
+pub struct Grenade {
+    pub kind: GrenadeKind,
+    pub rigid_body: BodyHandle,
+    position: Point2,
+    age_seconds: f32,
+}
+
+impl Grenade {
+    pub fn throw(world: &mut World<f32>, kind: GrenadeKind, from: Point2, target: Point2) -> Self {
+        let geom = ShapeHandle::new(Ball::new(GRENADE_RADIUS));
+        let inertia = geom.inertia(1.0);
+        let center_of_mass = geom.center_of_mass();
+
+        let pos = Isometry2::new(from.coords, 0.0);
+        let rb = world.add_rigid_body(pos, inertia, center_of_mass);
+
+        let collider = world.add_collider(
+            COLLIDER_MARGIN,
+            geom.clone(),
+            rb,
+            Isometry2::identity(),
+            Material::new(0.6, 0.2),
+        );
+
+        world
+            .collision_world_mut()
+            .set_collision_groups(collider, grenade_groups());
+
+        world
+            .rigid_body_mut(rb)
+            .unwrap()
+            .set_linear_velocity((target - from) / GRENADE_FLIGHT_SECONDS);
+
+        Grenade {
+            kind,
+            rigid_body: rb,
+            position: from,
+            age_seconds: 0.0,
+        }
+    }
+
+    fn airborne(&self) -> bool {
+        self.age_seconds < GRENADE_FLIGHT_SECONDS
+    }
+
+    pub fn fuse_done(&self) -> bool {
+        self.age_seconds >= GRENADE_FUSE_SECONDS
+    }
+
+    pub fn update(&mut self, world: &mut World<f32>, dt: f32) {
+        self.age_seconds += dt;
+
+        if let Some(rb) = world.rigid_body_mut(self.rigid_body) {
+            self.position = rb.position().translation.vector.into();
+
+            // Roll to a stop after landing
+            if !self.airborne() {
+                let mut vel = rb.velocity().clone();
+                vel.linear *= 0.92;
+                rb.set_velocity(vel);
+            }
+        }
+    }
+
+    pub fn detonate(
+        &self,
+        world: &World<f32>,
+        player_pos: Point2,
+        gas: &GasCloudConfig,
+    ) -> Detonation {
+        match self.kind {
+            GrenadeKind::TearGas => Detonation::Gas(GasCloud::new(gas, self.position)),
+            GrenadeKind::Flashbang => {
+                let dist = (player_pos - self.position).norm();
+                if dist < FLASHBANG_RADIUS
+                    && is_point_visible(world, self.position, player_pos, GROUP_PROJECTILE)
+                {
+                    Detonation::Flash(FLASHBANG_SECONDS * (1.0 - dist / FLASHBANG_RADIUS))
+                } else {
+                    Detonation::Flash(0.0)
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context) {
+        // Grow along the arc to sell the lob
+        let height = if self.airborne() {
+            (self.age_seconds / GRENADE_FLIGHT_SECONDS * std::f32::consts::PI).sin()
+        } else {
+            0.0
+        };
+
+        let color = match self.kind {
+            GrenadeKind::TearGas => Color::new(0.3, 0.35, 0.2, 1.0),
+            GrenadeKind::Flashbang => Color::new(0.2, 0.2, 0.25, 1.0),
+        };
+
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            self.position + Vector2::new(0.0, height * 0.5),
+            GRENADE_RADIUS * (1.0 + height),
+            0.01,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    }
+}
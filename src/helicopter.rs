@@ -0,0 +1,224 @@
+use crate::{
+    graphics, AiDebug, BodyHandle, Bullet, Color, Context, Enemy, Faction, Movement, Point2,
+    Positional, Settings, Sounds, StatusEffectConfig, StatusEffects, Vector2, World,
+};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct HelicopterConfig {
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub max_vel: f32,
+    pub spotlight_radius: f32,
+    // How quickly the spotlight catches up with the player, per frame
+    pub spotlight_tracking: f32,
+}
+
+impl Default for HelicopterConfig {
+    fn default() -> Self {
+        Self {
+            orbit_radius: 8.0,
+            orbit_speed: 0.3,
+            max_vel: 6.0,
+            spotlight_radius: 2.5,
+            spotlight_tracking: 0.03,
+        }
+    }
+}
+
+// Circles the player with a spotlight that marks them for everyone else
+pub struct Helicopter {
+    rigid_body: BodyHandle,
+    cfg: HelicopterConfig,
+    size: f32,
+    health: f32,
+    max_health: f32,
+    positional: Positional,
+    orbit_angle: f32,
+    spotlight: Point2,
+    rotor_angle: f32,
+    time_since_last_damage: f32,
+    status: StatusEffects,
+    faction: Faction,
+}
+
+impl Helicopter {
+    pub fn new(
+        rigid_body: BodyHandle,
+        cfg: HelicopterConfig,
+        size: f32,
+        health: f32,
+        pos: Point2,
+    ) -> Self {
+        Helicopter {
+            rigid_body,
+            cfg,
+            size,
+            health,
+            max_health: health,
+            positional: Positional {
+                position: pos,
+                rotation: 0.0,
+            },
+            orbit_angle: pos.y.atan2(pos.x),
+            spotlight: pos,
+            rotor_angle: 0.0,
+            time_since_last_damage: 10000.0,
+            status: StatusEffects::default(),
+            faction: Faction::Police,
+        }
+    }
+
+    pub fn set_faction(&mut self, faction: Faction) {
+        self.faction = faction;
+    }
+}
+
+impl Enemy for Helicopter {
+    fn update(
+        &mut self,
+        _settings: &Settings,
+        player_pos: Positional,
+        _movement: Option<Movement>,
+        world: &mut World<f32>,
+        _bullets_out: &mut Vec<Bullet>,
+        _sounds: &mut Sounds,
+    ) {
+        let dt = 1.0 / 60.0;
+
+        self.orbit_angle += self.cfg.orbit_speed * dt;
+        let goal = player_pos.position
+            + Vector2::new(self.orbit_angle.cos(), self.orbit_angle.sin()) * self.cfg.orbit_radius;
+
+        let rigid_body = world.rigid_body_mut(self.rigid_body).unwrap();
+        self.positional.set_from_physics(rigid_body);
+
+        let max_vel = self.cfg.max_vel * self.status.speed_scale();
+        let mut velocity = (goal - self.positional.position) * 2.0;
+        if velocity.norm() > max_vel {
+            velocity *= max_vel / velocity.norm();
+        }
+
+        // Nose into the direction of travel
+        let mut pos = rigid_body.position();
+        if velocity.norm() > 0.5 {
+            pos.rotation = nalgebra::UnitComplex::from_angle(velocity.y.atan2(velocity.x));
+        }
+
+        rigid_body.activate();
+        rigid_body.set_position(pos);
+        rigid_body.set_linear_velocity(velocity);
+        rigid_body.set_angular_velocity(0.0);
+
+        // A blinded crew loses track of the player until it clears
+        if !self.status.blinded() {
+            self.spotlight = Point2::from(
+                self.spotlight.coords
+                    + (player_pos.position - self.spotlight) * self.cfg.spotlight_tracking,
+            );
+        }
+
+        let status_damage = self.status.update(dt);
+        self.health = (self.health - status_damage).max(0.0);
+
+        self.rotor_angle += 25.0 * dt;
+        self.time_since_last_damage += dt;
+    }
+
+    fn rigid_body(&self) -> Option<BodyHandle> {
+        Some(self.rigid_body)
+    }
+
+    fn draw(&self, ctx: &mut Context) {
+        let spotlight = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            self.spotlight,
+            self.cfg.spotlight_radius,
+            0.05,
+            Color::new(1.0, 1.0, 0.7, 0.25),
+        )
+        .unwrap();
+        graphics::draw(ctx, &spotlight, graphics::DrawParam::new()).unwrap();
+
+        let pos = self.positional.position;
+        let forward = self.positional.forward();
+        let color = self.color();
+
+        let tail =
+            graphics::Mesh::new_line(ctx, &[pos, pos - forward * self.size], 0.25, color).unwrap();
+        graphics::draw(ctx, &tail, graphics::DrawParam::new()).unwrap();
+
+        let body = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            pos,
+            self.size * 0.35,
+            0.02,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &body, graphics::DrawParam::new()).unwrap();
+
+        for i in 0..2 {
+            let a = self.rotor_angle + i as f32 * std::f32::consts::FRAC_PI_2;
+            let blade = Vector2::new(a.cos(), a.sin()) * (self.size * 0.6);
+            let rotor = graphics::Mesh::new_line(
+                ctx,
+                &[pos - blade, pos + blade],
+                0.1,
+                Color::new(0.1, 0.1, 0.1, 0.8),
+            )
+            .unwrap();
+            graphics::draw(ctx, &rotor, graphics::DrawParam::new()).unwrap();
+        }
+    }
+
+    fn color(&self) -> Color {
+        let t = self.time_since_last_damage;
+        let t = (1.0 - t * 5.0).max(0.0) * 10.0;
+        let f = self.health / self.max_health;
+        self.status
+            .tint(Color::new(0.25 + t, 0.3 * f + t, 0.25 * f + t, 1.0))
+    }
+
+    fn health(&self) -> f32 {
+        self.health
+    }
+
+    fn damage(&mut self, amount: f32) {
+        self.health = (self.health - amount).max(0.0);
+        self.time_since_last_damage = 0.0;
+    }
+
+    fn alive(&self) -> bool {
+        self.health > 0.0
+    }
+
+    fn closest_target(&self) -> Option<Point2> {
+        Some(self.spotlight)
+    }
+
+    fn apply_status(&mut self, effect: &StatusEffectConfig) {
+        self.status.apply(effect);
+    }
+
+    fn faction(&self) -> Faction {
+        self.faction
+    }
+    fn positional(&self) -> Positional {
+        self.positional
+    }
+
+    fn spotlight(&self) -> Option<(Point2, f32)> {
+        Some((self.spotlight, self.cfg.spotlight_radius))
+    }
+
+    fn ai_debug(&self) -> AiDebug {
+        AiDebug {
+            state: "orbiting".to_string(),
+            target: Some(self.spotlight),
+            ..AiDebug::default()
+        }
+    }
+}
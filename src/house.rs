@@ -0,0 +1,62 @@
+use crate::{HouseMarker, Point2, Vector2};
+
+// Wall pieces within this distance of the house outline make up its walls
+const PERIMETER_BAND: f32 = 1.5;
+
+// The ranch house the player is defending; losing it loses the round
+pub struct RanchHouse {
+    pub center: Point2,
+    pub half_extents: Vector2,
+    pub health: f32,
+    pub max_health: f32,
+    time_since_last_damage: f32,
+}
+
+impl RanchHouse {
+    pub fn new(marker: &HouseMarker) -> Self {
+        RanchHouse {
+            center: marker.center,
+            half_extents: marker.half_extents,
+            health: marker.health,
+            max_health: marker.health,
+            time_since_last_damage: 10000.0,
+        }
+    }
+
+    pub fn contains(&self, pos: Point2) -> bool {
+        let rel = pos - self.center;
+        rel.x.abs() < self.half_extents.x && rel.y.abs() < self.half_extents.y
+    }
+
+    pub fn on_perimeter(&self, pos: Point2) -> bool {
+        let rel = pos - self.center;
+        let outside = Vector2::new(
+            rel.x.abs() - self.half_extents.x,
+            rel.y.abs() - self.half_extents.y,
+        );
+        outside.x.max(outside.y).abs() < PERIMETER_BAND
+    }
+
+    pub fn damage(&mut self, amount: f32) {
+        if amount > 0.0 {
+            self.health -= amount.min(self.health);
+            self.time_since_last_damage = 0.0;
+        }
+    }
+
+    pub fn alive(&self) -> bool {
+        self.health > 0.0
+    }
+
+    pub fn health_fraction(&self) -> f32 {
+        self.health / self.max_health
+    }
+
+    pub fn under_attack(&self) -> bool {
+        self.time_since_last_damage < 0.5
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time_since_last_damage += dt;
+    }
+}
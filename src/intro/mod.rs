@@ -34,11 +34,13 @@ impl IntroPhase {
         let player_stand = characters.transform(&player_entry.stand);
         let sheriff_stand = characters.transform(&sheriff_entry.stand);
 
-        let player_pos = Point2::new(9.120043, 3.124171);
-        let player_rot = -0.000054519685;
+        // Player and sheriff face each other
+        let player_pos = data.markers.intro_player;
+        let sheriff_pos = data.markers.intro_sheriff;
+        let to_sheriff = sheriff_pos - player_pos;
 
-        let sheriff_pos = Point2::new(11.19879, 3.0724447);
-        let sheriff_rot = -3.138286;
+        let player_rot = to_sheriff.y.atan2(to_sheriff.x);
+        let sheriff_rot = (-to_sheriff.y).atan2(-to_sheriff.x);
 
         let mut lines: Vec<IntroLine> = Vec::new();
 
@@ -194,38 +196,29 @@ impl IntroPhase {
 
         graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Background",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Background",
+            data.screen_to_world,
+        );
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Walls",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Walls",
+            data.screen_to_world,
+        );
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Props",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Props",
+            data.screen_to_world,
+        );
 
         let character_spritebatch = &mut *data.character_spritebatch.borrow_mut();
 
@@ -0,0 +1,131 @@
+use crate::{
+    clamp_norm, graphics, BodyHandle, Bullet, Color, Context, Enemy, Faction, Movement, PawnInput,
+    Player, Point2, Positional, Settings, Sounds, StatusEffectConfig, World,
+};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct K9Config {
+    pub chase_speed: f32,
+    pub bite_range: f32,
+}
+
+impl Default for K9Config {
+    fn default() -> Self {
+        Self {
+            chase_speed: 1.0,
+            bite_range: 1.2,
+        }
+    }
+}
+
+// Dogs track the player by scent, so hiding behind a wall doesn't shake them off
+pub struct K9 {
+    pawn: Player,
+    cfg: K9Config,
+    max_health: f32,
+}
+
+impl K9 {
+    pub fn new(pawn: Player, cfg: K9Config) -> Self {
+        K9 {
+            max_health: pawn.health,
+            pawn,
+            cfg,
+        }
+    }
+}
+
+impl Enemy for K9 {
+    fn update(
+        &mut self,
+        _settings: &Settings,
+        player_pos: Positional,
+        _movement: Option<Movement>,
+        world: &mut World<f32>,
+        bullets_out: &mut Vec<Bullet>,
+        _sounds: &mut Sounds,
+    ) {
+        let pos = self.pawn.positional.position;
+        let offset = player_pos.position - pos;
+        let in_range = offset.norm() < self.cfg.bite_range;
+
+        let movement = clamp_norm(offset, self.cfg.chase_speed);
+        self.pawn.set_input(PawnInput {
+            movement: Movement {
+                right: movement.x,
+                forward: movement.y,
+            },
+            shoot: in_range,
+            aim_pos: player_pos.position,
+        });
+
+        self.pawn.update(world, bullets_out);
+    }
+
+    fn rigid_body(&self) -> Option<BodyHandle> {
+        Some(self.pawn.body_handle)
+    }
+
+    // No dog in the character sheet, so they're drawn as shapes
+    fn draw(&self, ctx: &mut Context) {
+        let positional = &self.pawn.positional;
+        let color = self.color();
+
+        let body = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            positional.position,
+            0.3,
+            0.02,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &body, graphics::DrawParam::new()).unwrap();
+
+        let head = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            positional.position + positional.forward() * 0.35,
+            0.18,
+            0.02,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &head, graphics::DrawParam::new()).unwrap();
+    }
+
+    fn color(&self) -> Color {
+        let t = self.pawn.health / self.max_health;
+        self.pawn
+            .status
+            .tint(Color::new(0.45, 0.3 * t + 0.1, 0.15 * t + 0.05, 1.0))
+    }
+
+    fn health(&self) -> f32 {
+        self.pawn.health
+    }
+
+    fn damage(&mut self, amount: f32) {
+        self.pawn.damage(amount);
+    }
+
+    fn alive(&self) -> bool {
+        self.pawn.alive()
+    }
+
+    fn closest_target(&self) -> Option<Point2> {
+        None
+    }
+
+    fn apply_status(&mut self, effect: &StatusEffectConfig) {
+        self.pawn.status.apply(effect);
+    }
+
+    fn faction(&self) -> Faction {
+        self.pawn.faction()
+    }
+    fn positional(&self) -> Positional {
+        self.pawn.positional
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    graphics, Ball, BodyHandle, Color, Context, Isometry2, Point2, Rect, ShapeHandle, World,
+    GROUP_AIR, GROUP_ALLY, GROUP_ENEMY, GROUP_LIVESTOCK, GROUP_PICKUP, GROUP_PLAYER,
+    GROUP_PROJECTILE, GROUP_WORLD,
+};
+
+use ncollide2d::world::CollisionGroups;
+use nphysics2d::object::ColliderHandle;
+
+pub const PICKUP_RADIUS: f32 = 0.4;
+const PICKUP_LIFE_SECONDS: f32 = 30.0;
+
+pub const LOOTED_SMG: &str = "resources/looted_smg.toml";
+pub const AMMO_PICKUP_ROUNDS: u32 = 45;
+pub const MEDKIT_HEALTH: f32 = 0.35;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LootKind {
+    Ammo,
+    Medkit,
+    Smg,
+    // Salvaged from wrecked dozers; each piece is an extra crate next time you prepare
+    Scrap,
+}
+
+impl LootKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            LootKind::Ammo => "Ammo",
+            LootKind::Medkit => "Medkit",
+            LootKind::Smg => "SMG",
+            LootKind::Scrap => "Scrap",
+        }
+    }
+}
+
+pub struct Pickup {
+    pub kind: LootKind,
+    pub collider: ColliderHandle,
+    pub position: Point2,
+    pub life_seconds: f32,
+    // Whether the player is standing on it, tracked from sensor events
+    pub touching: bool,
+}
+
+impl Pickup {
+    pub fn spawn(world: &mut World<f32>, kind: LootKind, position: Point2) -> Self {
+        let collider = world.add_sensor(
+            ShapeHandle::new(Ball::new(PICKUP_RADIUS)),
+            BodyHandle::ground(),
+            Isometry2::new(position.coords, 0.0),
+        );
+
+        // Only the player's side can set it off; the blacklist also keeps rays from hitting it
+        let mut groups = CollisionGroups::new();
+        groups.set_membership(&[GROUP_PICKUP]);
+        groups.set_whitelist(&[GROUP_PLAYER]);
+        groups.set_blacklist(&[
+            GROUP_WORLD,
+            GROUP_ENEMY,
+            GROUP_ALLY,
+            GROUP_LIVESTOCK,
+            GROUP_PROJECTILE,
+            GROUP_AIR,
+            GROUP_PICKUP,
+        ]);
+        world
+            .collision_world_mut()
+            .set_collision_groups(collider, groups);
+
+        Pickup {
+            kind,
+            collider,
+            position,
+            life_seconds: PICKUP_LIFE_SECONDS,
+            touching: false,
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context) {
+        // Blink before despawning
+        if self.life_seconds < 5.0 && (self.life_seconds * 8.0).sin() < 0.0 {
+            return;
+        }
+
+        let color = match self.kind {
+            LootKind::Ammo => Color::new(0.9, 0.75, 0.2, 1.0),
+            LootKind::Medkit => Color::new(1.0, 1.0, 1.0, 1.0),
+            LootKind::Smg => Color::new(0.25, 0.25, 0.3, 1.0),
+            LootKind::Scrap => Color::new(0.55, 0.45, 0.35, 1.0),
+        };
+
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::fill(),
+            self.position,
+            PICKUP_RADIUS,
+            0.02,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+
+        if self.kind == LootKind::Medkit {
+            let p = self.position;
+            for rect in [
+                Rect::new(p.x - 0.25, p.y - 0.08, 0.5, 0.16),
+                Rect::new(p.x - 0.08, p.y - 0.25, 0.16, 0.5),
+            ]
+            .iter()
+            {
+                let cross = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    *rect,
+                    Color::new(0.8, 0.0, 0.0, 1.0),
+                )
+                .unwrap();
+                graphics::draw(ctx, &cross, graphics::DrawParam::new()).unwrap();
+            }
+        }
+    }
+}
@@ -14,24 +14,44 @@ use ggez::timer;
 use ggez::{Context, GameResult};
 use nalgebra as na;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::path::{self, Path};
+use std::path;
 use std::rc::Rc;
 
 mod ai;
+mod ally;
+mod archetype;
+mod behavior_tree;
 mod characters;
 mod consts;
 mod enemy;
+mod faction;
+mod hazard;
+mod helicopter;
+mod house;
+mod k9;
+mod loot;
+mod map_check;
+mod map_collision;
+mod mapgen;
+mod maps;
+mod markers;
 mod music;
 mod player;
 mod settings;
+mod sniper;
 mod sounds;
+mod status;
+mod tile_props;
 mod tile_util;
 mod types;
 mod voice;
+mod wall_anchor;
 mod weapon;
 
 mod dead;
+mod editor;
 mod intro;
 mod menu;
 mod outro;
@@ -39,19 +59,38 @@ mod prepare;
 mod round;
 
 use self::ai::*;
+use self::ally::*;
+use self::archetype::*;
+use self::behavior_tree::*;
 use self::characters::*;
 use self::consts::*;
 use self::enemy::*;
+use self::faction::*;
+use self::hazard::*;
+use self::helicopter::*;
+use self::house::*;
+use self::k9::*;
+use self::loot::*;
+use self::map_check::*;
+use self::map_collision::*;
+use self::mapgen::*;
+use self::maps::*;
+use self::markers::*;
 use self::music::*;
 use self::player::*;
 use self::settings::*;
+use self::sniper::*;
 use self::sounds::*;
+use self::status::*;
+use self::tile_props::*;
 use self::tile_util::*;
 use self::types::*;
 use self::voice::*;
+use self::wall_anchor::*;
 use self::weapon::*;
 
 use self::dead::*;
+use self::editor::*;
 use self::intro::*;
 use self::menu::*;
 use self::outro::*;
@@ -59,19 +98,23 @@ use self::prepare::*;
 use self::round::*;
 
 use na::Isometry2;
-use ncollide2d::shape::{Ball, Cuboid, ShapeHandle};
+use ncollide2d::shape::{Ball, Compound, Cuboid, ShapeHandle};
 use ncollide2d::world::CollisionGroups;
 use nphysics2d::algebra::Force2;
-use nphysics2d::force_generator::{ForceGeneratorHandle, Spring};
-use nphysics2d::object::{BodyHandle, Material};
+use nphysics2d::force_generator::ForceGeneratorHandle;
+use nphysics2d::object::{BodyHandle, ColliderHandle, Material};
 use nphysics2d::volumetric::Volumetric;
 use nphysics2d::world::World;
 
 pub const DESIRED_FPS: u32 = 60;
 pub const TIME_STEP: f32 = 1.0 / 60.0;
 
+// Strain a wall piece sheds per second; pushes gentler than this never break anything
+const WALL_STRAIN_RECOVERY: f32 = 1.0;
+
 enum Phase {
     Dead(DeadPhase),
+    Editor(EditorPhase),
     Intro(IntroPhase),
     Menu(MenuPhase),
     Outro(OutroPhase),
@@ -82,12 +125,18 @@ enum Phase {
 pub struct WorldData {
     world_to_screen: Matrix4,
     screen_to_world: Matrix4,
+    maps: Vec<MapInfo>,
+    map_index: usize,
     map: tiled::Map,
-    map_tile_image: graphics::Image,
-    map_spritebatch: graphics::spritebatch::SpriteBatch,
+    markers: MapMarkers,
+    map_graphics: MapGraphics,
+    wall_materials: WallMaterials,
+    tile_catalog: TileCatalog,
+    map_colliders: MapColliders,
     bullets: Vec<Bullet>,
     bullet_batch: SingleImageSpriteBatch,
     wall_pieces: Vec<WallPiece>,
+    house: Option<RanchHouse>,
     world: World<f32>,
     engine_data: audio::SoundData,
     font: graphics::Font,
@@ -96,26 +145,50 @@ pub struct WorldData {
     player: Player,
     player_input: PlayerInput,
     splash: graphics::Image,
-    dozer_image: Rc<graphics::Image>,
+    archetypes: EnemyArchetypes,
+    factions: FactionTable,
     enemies: Vec<Box<dyn Enemy>>,
+    allies: Vec<Ally>,
+    wrecks: Vec<Wreck>,
+    hazards: Vec<GasCloud>,
+    tear_gas: GasCloudConfig,
+    grenades: Vec<Grenade>,
+    pickups: Vec<Pickup>,
+    // Wall damage done by each enemy body, for the debug overlay
+    wall_damage_dealt: HashMap<BodyHandle, f32>,
+    // Salvage carried over into the next prepare phase
+    scrap: u32,
+    map_seed: Option<u64>,
+    gas_exposure: f32,
+    flash_seconds: f32,
     camera_pos: Point2,
     strategic_view: bool,
     character_spritebatch: Rc<RefCell<graphics::spritebatch::SpriteBatch>>,
 }
 
 impl WorldData {
-    pub fn new(_settings: settings::Settings, ctx: &mut Context) -> Self {
-        let map = tiled::parse_file(&Path::new("resources/map.tmx")).unwrap();
-        let map_tile_image =
-            Image::new(ctx, &map.tilesets[0].images[0].source).expect("opening the tileset image");
-        let map_spritebatch = graphics::spritebatch::SpriteBatch::new(map_tile_image.clone());
+    pub fn new(settings: settings::Settings, ctx: &mut Context) -> Self {
+        let maps = MapInfo::load_all(MAPS_DIR);
+        let map = load_map(&maps[0].path);
+        let markers = MapMarkers::from_map(&map);
+        let house = markers.ranch_house.as_ref().map(RanchHouse::new);
+        let map_graphics = MapGraphics::load(ctx, &map, &maps[0].path);
+        let wall_materials = WallMaterials::from_toml("resources/materials.toml");
+        let tile_catalog = TileCatalog::from_map(&map, &wall_materials);
+
+        let mut sounds = Sounds::load(ctx);
+        for sound in tile_catalog.breaking_sounds() {
+            sounds.load_named(ctx, &sound);
+        }
 
         let mut world = World::new();
         world.set_timestep(TIME_STEP);
 
+        let map_colliders = MapColliders::build(&mut world, &map, &tile_catalog, &map_graphics);
+
         let characters = Characters::load(ctx);
 
-        let dozer_image = Rc::new(graphics::Image::new(ctx, "/dozer_lores.png").unwrap());
+        let archetypes = EnemyArchetypes::load(ctx, "resources/enemies");
         let engine_sound = audio::SoundData::new(ctx, "/sound/bulldozer3.ogg").unwrap();
         let splash = graphics::Image::new(ctx, "/splash/hindranch_0.png").unwrap();
 
@@ -129,8 +202,8 @@ impl WorldData {
             "woman_green",
             health,
             Weapon::from_config(ctx, WeaponConfig::from_toml("resources/shotgun.toml")),
-            Point2::new(0.5, 0.5),
-            GROUP_PLAYER,
+            markers.player_start,
+            Faction::Player,
             &characters,
             character_spritebatch.clone(),
         );
@@ -145,22 +218,40 @@ impl WorldData {
         WorldData {
             world_to_screen: Matrix4::identity(),
             screen_to_world: Matrix4::identity(),
+            maps,
+            map_index: 0,
             map,
-            map_tile_image,
-            map_spritebatch,
+            markers,
+            map_graphics,
+            wall_materials,
+            tile_catalog,
+            map_colliders,
             bullets: Vec::new(),
             bullet_batch: SingleImageSpriteBatch::new(ctx, "/bullet.png"),
             wall_pieces: Vec::new(),
+            house,
             world,
             font,
             engine_data: engine_sound.clone(),
-            sounds: Sounds::load(ctx),
+            sounds,
             characters,
             player,
             player_input: PlayerInput::default(),
             splash,
-            dozer_image,
+            archetypes,
+            factions: FactionTable::from_toml("resources/factions.toml"),
             enemies: Vec::new(),
+            allies: Vec::new(),
+            wrecks: Vec::new(),
+            hazards: Vec::new(),
+            tear_gas: GasCloudConfig::from_toml("resources/tear_gas.toml"),
+            grenades: Vec::new(),
+            pickups: Vec::new(),
+            wall_damage_dealt: HashMap::new(),
+            scrap: 0,
+            map_seed: settings.map_seed,
+            gas_exposure: 0.0,
+            flash_seconds: 0.0,
             camera_pos: Point2::origin(),
             strategic_view: false,
             character_spritebatch,
@@ -172,30 +263,285 @@ impl WorldData {
             self.world.remove_bodies(&[enemy.rigid_body().unwrap()]);
         }
 
+        for ally in &self.allies {
+            self.world.remove_bodies(&[ally.pawn.body_handle]);
+        }
+
+        for wreck in &self.wrecks {
+            self.world.remove_bodies(&[wreck.rigid_body]);
+        }
+
+        for grenade in &self.grenades {
+            self.world.remove_bodies(&[grenade.rigid_body]);
+        }
+
+        for pickup in &self.pickups {
+            self.world.remove_colliders(&[pickup.collider]);
+        }
+
         self.bullets.clear();
         self.enemies.clear();
+        self.allies.clear();
+        self.wrecks.clear();
+        self.hazards.clear();
+        self.grenades.clear();
+        self.pickups.clear();
+        self.wall_damage_dealt.clear();
+        for wall_piece in self.wall_pieces.iter_mut() {
+            wall_piece.attacker = None;
+        }
+        self.player.status.clear();
+        self.gas_exposure = 0.0;
+        self.flash_seconds = 0.0;
     }
 
-    pub fn maintain_walls(&mut self) {
-        // Dampen wall piece physics and calculate damage
+    // Puts the house back at full health; it only carries damage between rounds of a campaign
+    pub fn reset_house(&mut self) {
+        self.house = self.markers.ranch_house.as_ref().map(RanchHouse::new);
+    }
+
+    // Swaps in another map from the catalog; wall pieces are respawned by the caller
+    pub fn load_map(&mut self, ctx: &mut Context, map_index: usize) {
+        self.clear_transients(ctx);
+
+        for wall_piece in &self.wall_pieces {
+            self.world.remove_bodies(&[wall_piece.rb]);
+            self.world.remove_force_generator(wall_piece.spring);
+        }
+        self.wall_pieces.clear();
+
+        if self.maps[map_index].generated {
+            let seed = self.map_seed.unwrap_or_else(rand::random);
+            write_generated_map(&self.maps[map_index].path, seed);
+
+            // Shown on the menu, so a good layout can be played again
+            self.maps[map_index].description = format!(
+                "Laid out from seed {}; start with `--seed {}` to play it again.",
+                seed, seed
+            );
+        }
+
+        self.map_index = map_index;
+        self.map = load_map(&self.maps[map_index].path);
+        self.markers = MapMarkers::from_map(&self.map);
+        self.reset_house();
+        self.map_graphics = MapGraphics::load(ctx, &self.map, &self.maps[map_index].path);
+        self.tile_catalog = TileCatalog::from_map(&self.map, &self.wall_materials);
+        for sound in self.tile_catalog.breaking_sounds() {
+            self.sounds.load_named(ctx, &sound);
+        }
+
+        self.map_colliders.remove(&mut self.world);
+        self.map_colliders = MapColliders::build(
+            &mut self.world,
+            &self.map,
+            &self.tile_catalog,
+            &self.map_graphics,
+        );
+
+        let player_start = self.markers.player_start;
+        if let Some(rb) = self.world.rigid_body_mut(self.player.body_handle) {
+            rb.set_position(Isometry2::new(player_start.coords, 0.0));
+        }
+        self.player.positional.position = player_start;
+    }
+
+    // Adds a physics piece for a wall tile, shaped and weighted by its tile properties and
+    // held in place by a spring as stiff as its material
+    pub fn add_wall_piece(&mut self, tile: u32, pos: Point2) {
+        let properties = self.tile_catalog.get(tile);
+
+        let (rb, collider, inertia) = {
+            let rad = tile_cell(&self.map) * 0.5 - Vector2::new(COLLIDER_MARGIN, COLLIDER_MARGIN);
+
+            let geom = if !properties.shapes.is_empty() {
+                ShapeHandle::new(Compound::new(properties.shapes.clone()))
+            } else {
+                match properties.shape {
+                    TileShape::Box => ShapeHandle::new(Cuboid::new(rad)),
+                    TileShape::Ball => ShapeHandle::new(Ball::new(rad.x.min(rad.y))),
+                }
+            };
+
+            // Volumetric works from a density; spread the tile's mass over its shape
+            let density = properties.mass / geom.mass(1.0);
+            let inertia = geom.inertia(density);
+            let center_of_mass = geom.center_of_mass();
+
+            let pos = Isometry2::new(pos.coords, na::zero());
+            let rb = self.world.add_rigid_body(pos, inertia, center_of_mass);
+
+            let collider_handle = self.world.add_collider(
+                COLLIDER_MARGIN,
+                geom.clone(),
+                rb,
+                Isometry2::identity(),
+                Material::new(properties.restitution, properties.friction),
+            );
+
+            let mut col_group = CollisionGroups::new();
+            col_group.set_membership(&[GROUP_WORLD]);
+            if !properties.collidable {
+                // Still drawn and kept in place by its spring, but nothing bumps into it
+                col_group.set_whitelist(&[]);
+            }
+            self.world
+                .collision_world_mut()
+                .set_collision_groups(collider_handle, col_group);
+
+            (rb, collider_handle, inertia)
+        };
+
+        let spring = self.world.add_force_generator(WallAnchor::new(
+            rb,
+            pos,
+            properties.stiffness,
+            inertia.linear,
+            inertia.angular,
+        ));
+
+        self.wall_pieces.push(WallPiece {
+            tile,
+            rb,
+            collider,
+            spring,
+            anchor: pos,
+            mass: inertia.linear,
+            hp: properties.hp,
+            properties,
+            last_velocity: Vector2::zeros(),
+            strain: 0.0,
+            attacker: None,
+        });
+    }
+
+    // Contacts the solver used on each wall piece in the last step: the body on the other
+    // side and the contact normal, pointing into the piece
+    fn wall_contacts(&self) -> Vec<Vec<(BodyHandle, Vector2)>> {
+        let pieces: HashMap<ColliderHandle, usize> = self
+            .wall_pieces
+            .iter()
+            .enumerate()
+            .map(|(i, wp)| (wp.collider, i))
+            .collect();
+        let mut contacts = vec![Vec::new(); self.wall_pieces.len()];
+
+        for (co1, co2, generator) in self.world.collision_world().contact_pairs() {
+            let piece1 = pieces.get(&co1.handle());
+            let piece2 = pieces.get(&co2.handle());
+            if piece1.is_none() && piece2.is_none() {
+                continue;
+            }
+
+            let mut manifolds = Vec::new();
+            generator.contacts(&mut manifolds);
+
+            // Normals point from the first object to the second
+            for &(piece, other_co, sign) in [(piece1, co2, -1.0), (piece2, co1, 1.0)].iter() {
+                let other = self.world.collider_body_handle(other_co.handle());
+                if let (Some(&piece), Some(other)) = (piece, other) {
+                    for manifold in &manifolds {
+                        for tracked in manifold.contacts() {
+                            if tracked.contact.depth >= 0.0 {
+                                contacts[piece].push((other, *tracked.contact.normal * sign));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        contacts
+    }
+
+    // Drops what the walls remember about a body leaving the world, since nphysics hands
+    // its handle out again to the next body added
+    pub fn forget_body(&mut self, body: BodyHandle) {
         for wall_piece in self.wall_pieces.iter_mut() {
-            if let Some(rb) = self.world.rigid_body_mut(wall_piece.rb) {
-                let mut vel = rb.velocity().clone();
+            if wall_piece.attacker == Some(body) {
+                wall_piece.attacker = None;
+            }
+        }
+        self.wall_damage_dealt.remove(&body);
+    }
 
-                let dmg = MainState::wall_velocity_to_damage(&vel.linear);
-                wall_piece.hp = (wall_piece.hp - dmg).max(0.0);
+    pub fn maintain_walls(&mut self) {
+        let contacts = self.wall_contacts();
+
+        // Enemies are blamed for the pieces they touch; a piece shoved into another passes
+        // the blame on
+        let enemies: HashSet<BodyHandle> =
+            self.enemies.iter().filter_map(|e| e.rigid_body()).collect();
+        let pieces: HashMap<BodyHandle, usize> = self
+            .wall_pieces
+            .iter()
+            .enumerate()
+            .map(|(i, wp)| (wp.rb, i))
+            .collect();
+        let attackers: Vec<Option<BodyHandle>> = contacts
+            .iter()
+            .map(|touching| {
+                touching.iter().find_map(|&(body, _)| {
+                    if enemies.contains(&body) {
+                        Some(body)
+                    } else {
+                        pieces
+                            .get(&body)
+                            .and_then(|&other| self.wall_pieces[other].attacker)
+                    }
+                })
+            })
+            .collect();
+
+        for ((wall_piece, touching), attacker) in
+            self.wall_pieces.iter_mut().zip(contacts).zip(attackers)
+        {
+            let rb = match self.world.rigid_body(wall_piece.rb) {
+                Some(rb) => rb,
+                None => continue,
+            };
+
+            let vel = rb.velocity().clone();
+
+            if touching.is_empty() {
+                wall_piece.strain = 0.0;
+                wall_piece.attacker = None;
+            } else {
+                // The change in velocity over the last step the anchor doesn't explain came
+                // from the contacts. Only the push along a contact normal counts, since that
+                // is all a contact can deliver; sliding doesn't wear a piece down.
+                let pos: Point2 = rb.position().translation.vector.into();
+                let anchor_accel = anchor_acceleration(
+                    wall_piece.anchor,
+                    wall_piece.properties.stiffness,
+                    wall_piece.mass,
+                    pos - vel.linear * TIME_STEP,
+                    wall_piece.last_velocity,
+                );
+                let delta = vel.linear - wall_piece.last_velocity - anchor_accel * TIME_STEP;
+                let velocity_change = touching
+                    .iter()
+                    .map(|(_, normal)| delta.dot(normal).max(0.0))
+                    .fold(0.0, f32::max);
+
+                if attacker.is_some() {
+                    wall_piece.attacker = attacker;
+                }
+
+                let dmg = wall_piece.absorb(velocity_change);
+                if wall_piece.properties.destructible && dmg > 0.0 {
+                    wall_piece.hp = (wall_piece.hp - dmg).max(0.0);
+                    if let Some(enemy) = wall_piece.attacker {
+                        *self.wall_damage_dealt.entry(enemy).or_insert(0.0) += dmg;
+                    }
+                }
 
                 if dmg > 0.1 {
                     self.sounds.play_crash();
                 }
-
-                vel.linear *= 0.98;
-                vel.angular *= 0.95;
-                rb.set_velocity(vel);
-                let mut pos = rb.position().clone();
-                pos.rotation = nalgebra::UnitComplex::from_angle(pos.rotation.angle() * 0.95);
-                rb.set_position(pos);
             }
+
+            wall_piece.last_velocity = vel.linear;
         }
 
         let wall_pieces_to_remove: Vec<_> = self
@@ -206,10 +552,12 @@ impl WorldData {
             .collect();
 
         for i in wall_pieces_to_remove.into_iter().rev() {
-            let wp = &self.wall_pieces[i];
+            let wp = self.wall_pieces.swap_remove(i);
             self.world.remove_bodies(&[wp.rb]);
             self.world.remove_force_generator(wp.spring);
-            self.wall_pieces.swap_remove(i);
+            if let Some(ref sound) = wp.properties.breaking_sound {
+                self.sounds.play_named(sound);
+            }
         }
     }
 }
@@ -246,10 +594,42 @@ impl From<&PlayerInput> for PawnInput {
 }
 
 struct WallPiece {
-    tile_snip: Rect,
+    // Global tile id of the sprite
+    tile: u32,
     rb: BodyHandle,
+    collider: ColliderHandle,
     spring: ForceGeneratorHandle,
+    // Where the spring pulls the piece back to
+    anchor: Point2,
+    mass: f32,
     hp: f32,
+    properties: TileProperties,
+    // Velocity the piece went into the last physics step with
+    last_velocity: Vector2,
+    // Velocity change forced on the piece since it was first touched
+    strain: f32,
+    // Enemy body behind the current contact
+    attacker: Option<BodyHandle>,
+}
+
+impl WallPiece {
+    // Adds the velocity change contacts forced on the piece over one step to the strain and
+    // returns the damage it deals. Damage starts once the strain passes the material's
+    // threshold, so a long slow push adds up like one hard hit.
+    //
+    // This stands in for the contact impulse per unit of mass: nphysics 0.9 doesn't hand
+    // out the solver's impulses, so the velocity change is read off the piece instead,
+    // less what its anchor accounts for.
+    fn absorb(&mut self, velocity_change: f32) -> f32 {
+        let threshold = self.properties.damage_threshold;
+        self.strain = (self.strain - WALL_STRAIN_RECOVERY * TIME_STEP).max(0.0);
+
+        let before = (self.strain - threshold).max(0.0);
+        self.strain += velocity_change;
+        let after = (self.strain - threshold).max(0.0);
+
+        self.properties.damage_scale * (after - before)
+    }
 }
 
 pub fn draw_shadowed_text(ctx: &mut Context, pos: Point2, text: &graphics::Text, color: Color) {
@@ -332,11 +712,14 @@ struct MainState {
 
 impl MainState {
     fn new(settings: settings::Settings, ctx: &mut Context) -> GameResult<MainState> {
+        let world_data = WorldData::new(settings.clone(), ctx);
+        let round_count = world_data.maps[world_data.map_index].recommended_rounds;
+
         let mut s = MainState {
-            world_data: WorldData::new(settings.clone(), ctx),
+            world_data,
             settings: settings.clone(),
             phase: Phase::Menu(MenuPhase::new(ctx)),
-            round_count: 5,
+            round_count,
             round_index: 0,
         };
 
@@ -345,6 +728,12 @@ impl MainState {
         Ok(s)
     }
 
+    fn load_map(&mut self, ctx: &mut Context, map_index: usize) {
+        self.world_data.load_map(ctx, map_index);
+        self.spawn_wall_pieces();
+        self.round_count = self.world_data.maps[map_index].recommended_rounds;
+    }
+
     /// Apply the calculated view transform to the current graphics context
     pub fn apply_view_transform(ctx: &mut Context, world_to_screen: Matrix4) {
         graphics::set_transform(ctx, world_to_screen);
@@ -352,76 +741,32 @@ impl MainState {
     }
 
     fn spawn_wall_pieces(&mut self) {
-        let view = TileMapLayerView::new(get_map_layer(&self.world_data.map, "Walls"));
-
-        for MapTile { tile_id, pos } in view.iter() {
-            let src = tile_id_to_src_rect(
-                tile_id,
-                &self.world_data.map,
-                &self.world_data.map_tile_image,
-            );
-
-            let rb = {
-                let rad = 0.5 - COLLIDER_MARGIN;
-
-                // Sim as balls for less coupling between elements
-                //let geom = ShapeHandle::new(Ball::new(rad));
-                let geom = ShapeHandle::new(Cuboid::new(Vector2::new(rad, rad)));
-
-                let inertia = geom.inertia(10.0);
-                let center_of_mass = geom.center_of_mass();
-
-                let pos = Isometry2::new(pos.coords, na::zero());
-                let rb = self
-                    .world_data
-                    .world
-                    .add_rigid_body(pos, inertia, center_of_mass);
-
-                let collider_handle = self.world_data.world.add_collider(
-                    COLLIDER_MARGIN,
-                    geom.clone(),
-                    rb,
-                    Isometry2::identity(),
-                    Material::new(0.3, 0.0),
-                );
-
-                let mut col_group = CollisionGroups::new();
-                col_group.set_membership(&[GROUP_WORLD]);
-                self.world_data
-                    .world
-                    .collision_world_mut()
-                    .set_collision_groups(collider_handle, col_group);
+        let map = &self.world_data.map;
+        let view = match get_map_layer(map, "Walls") {
+            Some(layer) => TileMapLayerView::new(map, layer),
+            None => return,
+        };
+        let offset = self.world_data.map_graphics.layer_offset(map, "Walls");
 
-                rb
-            };
+        let tiles: Vec<(u32, Point2)> = view
+            .iter()
+            .map(|MapTile { tile_id, pos }| (tile_id, pos + offset))
+            .collect();
 
-            let spring = self.world_data.world.add_force_generator(Spring::new(
-                BodyHandle::ground(),
-                rb,
-                pos,
-                Point2::origin(),
-                0.0,
-                100.0,
-            ));
-
-            self.world_data.wall_pieces.push(WallPiece {
-                tile_snip: src,
-                rb,
-                spring,
-                hp: 1.0,
-            });
+        for (tile, pos) in tiles {
+            self.world_data.add_wall_piece(tile, pos);
         }
     }
 
     fn draw_wall_pieces(
         wall_pieces: &[WallPiece],
         world: &World<f32>,
-        sprite_batch: &mut graphics::spritebatch::SpriteBatch,
+        map_graphics: &mut MapGraphics,
+        map: &tiled::Map,
     ) {
-        for wall_piece in wall_pieces.iter() {
-            let tile_width = 64; // TODO
-            let scale = 1.0 / tile_width as f32;
+        let half_cell = tile_cell(map) * 0.5;
 
+        for wall_piece in wall_pieces.iter() {
             let (pos, rot): (Point2, f32) = {
                 let positional = world.rigid_body(wall_piece.rb).unwrap().position();
                 (
@@ -430,28 +775,24 @@ impl MainState {
                 )
             };
 
+            let health = wall_piece.hp / wall_piece.properties.hp.max(0.001);
             let color = graphics::Color {
                 r: 1.0,
-                g: wall_piece.hp,
-                b: wall_piece.hp,
+                g: health,
+                b: health,
                 a: 1.0,
             };
 
-            sprite_batch.add(
+            map_graphics.add(
+                map,
+                wall_piece.tile,
                 graphics::DrawParam::new()
-                    .src(wall_piece.tile_snip)
-                    .dest(pos - Vector2::new(0.5, 0.5))
-                    .scale(Vector2::new(scale, -scale))
+                    .dest(pos - half_cell)
                     .rotation(rot)
-                    .offset(Point2::new(0.5, 0.5))
                     .color(color),
             );
         }
     }
-
-    fn wall_velocity_to_damage(vel: &Vector2) -> f32 {
-        (0.1 * (vel.norm() - 4.0)).max(0.0)
-    }
 }
 
 impl event::EventHandler for MainState {
@@ -480,14 +821,54 @@ impl event::EventHandler for MainState {
                         )));
                     }
                 }
+                Phase::Editor(ref mut phase) => {
+                    phase.update(&self.settings, &mut self.world_data, ctx);
+                    if phase.test_play || phase.exit {
+                        let test_play = phase.test_play;
+
+                        // Reload from disk so wall pieces, colliders and markers match the
+                        // saved file; leaving without saving drops the edits
+                        let map_index = self.world_data.map_index;
+                        self.load_map(ctx, map_index);
+
+                        if test_play {
+                            self.round_index = 0;
+                            let last_round = self.round_count == 1;
+                            let round_data = Rc::new(RefCell::new(RoundData::new(ctx)));
+                            next_phase = Some(Phase::Prepare(PreparePhase::new(
+                                ctx,
+                                self.round_index,
+                                last_round,
+                                round_data,
+                            )));
+                        } else {
+                            next_phase = Some(Phase::Menu(MenuPhase::new(ctx)));
+                        }
+                    }
+                }
                 Phase::Menu(ref mut phase) => {
                     phase.update(&self.settings, &mut self.world_data, ctx);
-                    if phase.start_game {
-                        // Reset round index
-                        self.round_index = 0;
+                    if phase.start_game || phase.open_editor {
+                        let map_index = phase.map_index;
+                        let open_editor = phase.open_editor;
+
+                        if map_index != self.world_data.map_index
+                            || self.world_data.maps[map_index].generated
+                        {
+                            self.load_map(ctx, map_index);
+                        }
+
+                        if open_editor {
+                            next_phase = Some(Phase::Editor(EditorPhase::new(ctx)));
+                        } else {
+                            // Reset round index and the house
+                            self.round_index = 0;
+                            self.world_data.reset_house();
 
-                        // Player wants to start the game; go to intro
-                        next_phase = Some(Phase::Intro(IntroPhase::new(&mut self.world_data, ctx)));
+                            // Player wants to start the game; go to intro
+                            next_phase =
+                                Some(Phase::Intro(IntroPhase::new(&mut self.world_data, ctx)));
+                        }
                     }
                 }
                 Phase::Outro(ref mut phase) => {
@@ -551,6 +932,7 @@ impl event::EventHandler for MainState {
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         match self.phase {
             Phase::Dead(ref mut phase) => phase.draw(&self.settings, &mut self.world_data, ctx),
+            Phase::Editor(ref mut phase) => phase.draw(&self.settings, &mut self.world_data, ctx),
             Phase::Intro(ref mut phase) => phase.draw(&self.settings, &mut self.world_data, ctx),
             Phase::Menu(ref mut phase) => phase.draw(&self.settings, &mut self.world_data, ctx),
             Phase::Outro(ref mut phase) => phase.draw(&self.settings, &mut self.world_data, ctx),
@@ -569,6 +951,9 @@ impl event::EventHandler for MainState {
             Phase::Dead(ref mut phase) => {
                 phase.mouse_motion_event(&mut self.world_data, ctx, x, y, xrel, yrel)
             }
+            Phase::Editor(ref mut phase) => {
+                phase.mouse_motion_event(&mut self.world_data, ctx, x, y, xrel, yrel)
+            }
             Phase::Intro(ref mut phase) => {
                 phase.mouse_motion_event(&mut self.world_data, ctx, x, y, xrel, yrel)
             }
@@ -598,6 +983,9 @@ impl event::EventHandler for MainState {
             Phase::Dead(ref mut phase) => {
                 phase.mouse_button_down_event(&mut self.world_data, ctx, button, x, y)
             }
+            Phase::Editor(ref mut phase) => {
+                phase.mouse_button_down_event(&mut self.world_data, ctx, button, x, y)
+            }
             Phase::Intro(ref mut phase) => {
                 phase.mouse_button_down_event(&mut self.world_data, ctx, button, x, y)
             }
@@ -627,6 +1015,9 @@ impl event::EventHandler for MainState {
             Phase::Dead(ref mut phase) => {
                 phase.mouse_button_up_event(&mut self.world_data, ctx, button, x, y)
             }
+            Phase::Editor(ref mut phase) => {
+                phase.mouse_button_up_event(&mut self.world_data, ctx, button, x, y)
+            }
             Phase::Intro(ref mut phase) => {
                 phase.mouse_button_up_event(&mut self.world_data, ctx, button, x, y)
             }
@@ -660,6 +1051,9 @@ impl event::EventHandler for MainState {
             Phase::Dead(ref mut phase) => {
                 phase.handle_key(&self.settings, &mut self.world_data, ctx, key_code, true)
             }
+            Phase::Editor(ref mut phase) => {
+                phase.handle_key(&self.settings, &mut self.world_data, ctx, key_code, true)
+            }
             Phase::Intro(ref mut phase) => {
                 phase.handle_key(&self.settings, &mut self.world_data, ctx, key_code, true)
             }
@@ -683,6 +1077,9 @@ impl event::EventHandler for MainState {
             Phase::Dead(ref mut phase) => {
                 phase.handle_key(&self.settings, &mut self.world_data, ctx, key_code, false)
             }
+            Phase::Editor(ref mut phase) => {
+                phase.handle_key(&self.settings, &mut self.world_data, ctx, key_code, false)
+            }
             Phase::Intro(ref mut phase) => {
                 phase.handle_key(&self.settings, &mut self.world_data, ctx, key_code, false)
             }
@@ -725,8 +1122,6 @@ pub fn resolution() -> (f32, f32) {
 }
 
 pub fn main() -> GameResult {
-    let settings = settings::load_settings();
-
     let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
         path.push("resources");
@@ -735,6 +1130,41 @@ pub fn main() -> GameResult {
         path::PathBuf::from("./resources")
     };
 
+    // `--validate-map path.tmx` checks a map and exits without opening a window
+    let args: Vec<String> = env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--validate-map") {
+        let map_path = match args.get(i + 1) {
+            Some(map_path) => path::PathBuf::from(map_path),
+            None => {
+                eprintln!("usage: hindranch --validate-map path.tmx");
+                std::process::exit(2);
+            }
+        };
+
+        let problems = validate_map(&map_path, &resource_dir);
+        if problems.is_empty() {
+            println!("{}: OK", map_path.display());
+            return Ok(());
+        }
+
+        println!("{}: {} problem(s)", map_path.display(), problems.len());
+        for problem in &problems {
+            println!("  - {}", problem);
+        }
+        std::process::exit(1);
+    }
+
+    let mut settings = settings::load_settings();
+    if let Some(i) = args.iter().position(|arg| arg == "--seed") {
+        match args.get(i + 1).and_then(|seed| seed.parse().ok()) {
+            Some(seed) => settings.map_seed = Some(seed),
+            None => {
+                eprintln!("usage: hindranch --seed <number>");
+                std::process::exit(2);
+            }
+        }
+    }
+
     let (width, height) = resolution();
 
     let cb = ggez::ContextBuilder::new("hindranch", "ggez")
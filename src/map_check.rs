@@ -0,0 +1,323 @@
+use crate::{
+    get_map_layer, reachable_cells, solid_prop_cells, tileset_image_path, world_to_map_cell,
+    EnemyArchetype, MapMarkers, Point2, SpawnShape, TileCatalog, Vector2, WallMaterials, GID_MASK,
+};
+use std::path::Path;
+
+// Tile layers a playable map needs; `Props` and the object layers are optional
+const REQUIRED_LAYERS: [&str; 2] = ["Background", "Walls"];
+
+// Points tried around ring spawn zones
+const RING_SAMPLES: usize = 16;
+
+// Checks a map without opening a window and returns every problem found, so a broken
+// map can be fixed in one go instead of one panic at a time
+pub fn validate_map(path: &Path, resource_dir: &Path) -> Vec<String> {
+    let map = match tiled::parse_file(path) {
+        Ok(map) => map,
+        Err(err) => return vec![format!("failed to parse: {:?}", err)],
+    };
+
+    let mut problems = Vec::new();
+    check_layers(&map, &mut problems);
+    check_tilesets(&map, resource_dir, &mut problems);
+    check_tile_ids(&map, &mut problems);
+    check_spawns(&map, resource_dir, &mut problems);
+    problems
+}
+
+fn check_layers(map: &tiled::Map, problems: &mut Vec<String>) {
+    for name in REQUIRED_LAYERS.iter() {
+        if get_map_layer(map, name).is_none() {
+            problems.push(format!("missing tile layer `{}`", name));
+        }
+    }
+
+    for layer in &map.layers {
+        let height = layer.tiles.len();
+        if height != map.height as usize
+            || layer
+                .tiles
+                .iter()
+                .any(|row| row.len() != map.width as usize)
+        {
+            problems.push(format!(
+                "layer `{}` is not {}x{} tiles like the map",
+                layer.name, map.width, map.height
+            ));
+        }
+    }
+
+    if let Some(walls) = get_map_layer(map, "Walls") {
+        let wall_count = walls
+            .tiles
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|&&gid| gid & GID_MASK != 0)
+            .count();
+        if wall_count == 0 {
+            problems.push("layer `Walls` has no tiles".to_owned());
+        }
+    }
+}
+
+fn check_tilesets(map: &tiled::Map, resource_dir: &Path, problems: &mut Vec<String>) {
+    if map.tilesets.is_empty() {
+        problems.push("map has no tilesets".to_owned());
+    }
+
+    for tileset in &map.tilesets {
+        if let Err(problem) = tile_count(tileset) {
+            problems.push(problem);
+        }
+
+        match tileset_image_path(tileset) {
+            Some(image_path) => {
+                let file = resource_dir.join(image_path.trim_start_matches('/'));
+                if !file.is_file() {
+                    problems.push(format!(
+                        "tileset `{}` image {} not found in {}",
+                        tileset.name,
+                        image_path,
+                        resource_dir.display()
+                    ));
+                }
+            }
+            None => problems.push(format!("tileset `{}` has no image", tileset.name)),
+        }
+    }
+}
+
+// Tiles that fit along one side of a tileset image
+fn tile_span(image_size: i32, tile_size: u32, margin: u32, spacing: u32) -> Option<u32> {
+    (image_size.max(0) as u32)
+        .checked_sub(margin.checked_mul(2)?)?
+        .checked_add(spacing)?
+        .checked_div(tile_size.checked_add(spacing)?)
+}
+
+// Number of tiles in a tileset, or why its image can't be cut into tiles
+fn tile_count(tileset: &tiled::Tileset) -> Result<u32, String> {
+    let image = match tileset.images.first() {
+        Some(image) => image,
+        // Reported as missing elsewhere; don't flag its tiles as well
+        None => return Ok(std::u32::MAX - tileset.first_gid),
+    };
+
+    let columns = tile_span(
+        image.width,
+        tileset.tile_width,
+        tileset.margin,
+        tileset.spacing,
+    );
+    let rows = tile_span(
+        image.height,
+        tileset.tile_height,
+        tileset.margin,
+        tileset.spacing,
+    );
+
+    match (columns, rows) {
+        (Some(columns), Some(rows)) => Ok(columns.saturating_mul(rows)),
+        _ => Err(format!(
+            "tileset `{}` can't be cut into {}x{} tiles with margin {} and spacing {} from a {}x{} image",
+            tileset.name,
+            tileset.tile_width,
+            tileset.tile_height,
+            tileset.margin,
+            tileset.spacing,
+            image.width,
+            image.height
+        )),
+    }
+}
+
+// Range of gids each tileset provides, sorted by first gid
+fn tileset_ranges(map: &tiled::Map) -> Vec<(u32, u32)> {
+    let mut ranges: Vec<(u32, u32)> = map
+        .tilesets
+        .iter()
+        .map(|tileset| {
+            // Broken tilesets are already reported; don't flag their tiles as well
+            let count = tile_count(tileset).unwrap_or(std::u32::MAX - tileset.first_gid);
+            (tileset.first_gid, tileset.first_gid.saturating_add(count))
+        })
+        .collect();
+    ranges.sort();
+    ranges
+}
+
+fn check_tile_ids(map: &tiled::Map, problems: &mut Vec<String>) {
+    let ranges = tileset_ranges(map);
+    let valid = |gid: u32| {
+        ranges
+            .iter()
+            .rev()
+            .find(|(first, _)| *first <= gid)
+            .map_or(false, |(_, end)| gid < *end)
+    };
+
+    for layer in &map.layers {
+        let mut bad_count = 0;
+        let mut first_bad = None;
+
+        for (row, tiles) in layer.tiles.iter().enumerate() {
+            for (column, &gid) in tiles.iter().enumerate() {
+                let gid = gid & GID_MASK;
+                if gid != 0 && !valid(gid) {
+                    bad_count += 1;
+                    first_bad.get_or_insert((gid, column, row));
+                }
+            }
+        }
+
+        if let Some((gid, column, row)) = first_bad {
+            problems.push(format!(
+                "layer `{}` has {} tile(s) outside every tileset (first: gid {} at column {}, row {})",
+                layer.name, bad_count, gid, column, row
+            ));
+        }
+    }
+}
+
+// Cells nothing can drive through: solid props and indestructible solid walls
+fn blocked_cells(map: &tiled::Map, materials: &WallMaterials) -> Vec<Vec<bool>> {
+    let catalog = TileCatalog::from_map(map, materials);
+    let mut blocked = solid_prop_cells(map, &catalog);
+
+    if let Some(layer) = get_map_layer(map, "Walls") {
+        for (row, tiles) in layer.tiles.iter().enumerate().take(map.height as usize) {
+            for (column, &gid) in tiles.iter().enumerate().take(map.width as usize) {
+                let gid = gid & GID_MASK;
+                if gid != 0 {
+                    let tile = catalog.get(gid);
+                    blocked[row][column] |= tile.collidable && !tile.destructible;
+                }
+            }
+        }
+    }
+
+    blocked
+}
+
+// Every spawn point must be on the map and connected to the player start, since
+// that is where the fight takes place
+fn check_spawns(map: &tiled::Map, resource_dir: &Path, problems: &mut Vec<String>) {
+    let has_marker = |kind: &str| {
+        map.object_groups
+            .iter()
+            .flat_map(|group| group.objects.iter())
+            .any(|object| object.obj_type == kind)
+    };
+
+    if !has_marker("player_start") {
+        problems.push("missing `player_start` marker".to_owned());
+    }
+
+    let markers = MapMarkers::from_map(map);
+    let reached = match reachable_from_start(map, &markers, resource_dir) {
+        Ok(reached) => Some(reached),
+        Err(problem) => {
+            problems.push(problem);
+            None
+        }
+    };
+
+    // Without a usable player start, spawns can only be checked for being on the map
+    let problem_at = |pos: Point2| match (world_to_map_cell(map, pos), reached.as_ref()) {
+        (None, _) => Some("is outside the map"),
+        (Some((x, y)), Some(reached)) if !reached[y][x] => {
+            Some("can't be reached from the player start")
+        }
+        _ => None,
+    };
+
+    let singles = [
+        ("intro_player", markers.intro_player),
+        ("intro_sheriff", markers.intro_sheriff),
+    ];
+    for (kind, pos) in singles.iter() {
+        if let Some(problem) = problem_at(*pos).filter(|_| has_marker(*kind)) {
+            problems.push(format!(
+                "`{}` at ({:.1}, {:.1}) {}",
+                kind, pos.x, pos.y, problem
+            ));
+        }
+    }
+
+    let mut known_kinds = vec!["ally".to_owned()];
+    if let Ok(entries) = std::fs::read_dir(resource_dir.join("enemies")) {
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match EnemyArchetype::try_from_toml(&path) {
+                Ok(archetype) => known_kinds.push(archetype.name),
+                Err(problem) => problems.push(problem),
+            }
+        }
+    }
+
+    for zone in markers.zones() {
+        if !known_kinds.contains(&zone.kind) {
+            problems.push(format!(
+                "spawn zone at ({:.1}, {:.1}) has unknown type `{}`",
+                zone.center.x, zone.center.y, zone.kind
+            ));
+        }
+
+        let samples: Vec<Point2> = match zone.shape {
+            SpawnShape::Ring { radii } => (0..RING_SAMPLES)
+                .map(|i| {
+                    let a = i as f32 / RING_SAMPLES as f32 * std::f32::consts::PI * 2.0;
+                    zone.center + radii.component_mul(&Vector2::new(a.cos(), a.sin()))
+                })
+                .collect(),
+            _ => vec![zone.center],
+        };
+
+        // One usable point is enough
+        let sample_problems: Vec<_> = samples.iter().map(|&pos| problem_at(pos)).collect();
+        if sample_problems.iter().all(Option::is_some) {
+            let problem = if sample_problems
+                .iter()
+                .all(|&p| p == Some("is outside the map"))
+            {
+                "is outside the map"
+            } else {
+                "can't be reached from the player start"
+            };
+            problems.push(format!(
+                "`{}` spawn zone at ({:.1}, {:.1}) {}",
+                zone.kind, zone.center.x, zone.center.y, problem
+            ));
+        }
+    }
+
+    if let Some(ref house) = markers.ranch_house {
+        if world_to_map_cell(map, house.center).is_none() {
+            problems.push("`ranch_house` is outside the map".to_owned());
+        }
+    }
+}
+
+// Cells that can be reached from the player start
+fn reachable_from_start(
+    map: &tiled::Map,
+    markers: &MapMarkers,
+    resource_dir: &Path,
+) -> Result<Vec<Vec<bool>>, String> {
+    let materials_path = resource_dir.join("materials.toml");
+    let materials = WallMaterials::try_from_toml(&materials_path.to_string_lossy())?;
+    let blocked = blocked_cells(map, &materials);
+
+    match world_to_map_cell(map, markers.player_start) {
+        Some(start) if !blocked[start.1][start.0] => Ok(reachable_cells(&blocked, &[start])),
+        Some(_) => Err("`player_start` is inside a solid tile".to_owned()),
+        None => Err("`player_start` is outside the map".to_owned()),
+    }
+}
@@ -0,0 +1,185 @@
+use super::consts::*;
+use super::tile_props::*;
+use super::tile_util::*;
+use super::types::*;
+use crate::{Ball, CollisionGroups, Cuboid, Isometry2, Material, ShapeHandle, World};
+use nphysics2d::object::{BodyHandle, ColliderHandle};
+
+// Half thickness of the walls around the map edges
+const EDGE_HALF_THICKNESS: f32 = 1.0;
+
+// Static colliders owned by the map: solid props and the walls along the map edges
+pub struct MapColliders {
+    colliders: Vec<ColliderHandle>,
+}
+
+impl MapColliders {
+    pub fn build(
+        world: &mut World<f32>,
+        map: &tiled::Map,
+        catalog: &TileCatalog,
+        map_graphics: &MapGraphics,
+    ) -> Self {
+        let mut colliders = Vec::new();
+
+        if let Some(layer) = get_map_layer(map, "Props") {
+            add_prop_colliders(
+                world,
+                map,
+                layer,
+                catalog,
+                map_graphics.layer_offset(map, "Props"),
+                &mut colliders,
+            );
+        }
+
+        let (min, max) = map_bounds(map);
+        let center = Point2::from((min.coords + max.coords) * 0.5);
+        let half = (max - min) * 0.5;
+        let t = EDGE_HALF_THICKNESS;
+
+        let edges = [
+            (
+                Vector2::new(0.0, half.y + t),
+                Vector2::new(half.x + 2.0 * t, t),
+            ),
+            (
+                Vector2::new(0.0, -half.y - t),
+                Vector2::new(half.x + 2.0 * t, t),
+            ),
+            (Vector2::new(half.x + t, 0.0), Vector2::new(t, half.y)),
+            (Vector2::new(-half.x - t, 0.0), Vector2::new(t, half.y)),
+        ];
+
+        for (offset, half_extents) in edges.iter() {
+            colliders.push(add_static_collider(
+                world,
+                Isometry2::new(center.coords + offset, 0.0),
+                ShapeHandle::new(Cuboid::new(*half_extents)),
+                Material::new(0.3, 0.0),
+            ));
+        }
+
+        MapColliders { colliders }
+    }
+
+    pub fn remove(&self, world: &mut World<f32>) {
+        world.remove_colliders(&self.colliders);
+    }
+}
+
+fn add_static_collider(
+    world: &mut World<f32>,
+    pos: Isometry2<f32>,
+    shape: ShapeHandle<f32>,
+    material: Material<f32>,
+) -> ColliderHandle {
+    let collider = world.add_collider(COLLIDER_MARGIN, shape, BodyHandle::ground(), pos, material);
+
+    let mut col_group = CollisionGroups::new();
+    col_group.set_membership(&[GROUP_WORLD]);
+    world
+        .collision_world_mut()
+        .set_collision_groups(collider, col_group);
+
+    collider
+}
+
+// Props only collide when the tileset tags them. Plain solid tiles are merged into as
+// few boxes as possible; balls and tiles with their own collision objects are added
+// one by one.
+fn add_prop_colliders(
+    world: &mut World<f32>,
+    map: &tiled::Map,
+    layer: &tiled::Layer,
+    catalog: &TileCatalog,
+    offset: Vector2,
+    colliders: &mut Vec<ColliderHandle>,
+) {
+    let view = TileMapLayerView::new(map, layer);
+    let cell = view.cell;
+    let width = view.end_x as usize;
+    let height = view.end_y as usize;
+
+    // Friction and restitution of every solid cell; only cells with matching materials merge
+    let mut solid: Vec<Option<(f32, f32)>> = vec![None; width * height];
+
+    for MapTile { tile_id, pos } in view.iter() {
+        let properties = match catalog.find(tile_id) {
+            Some(properties) if properties.collidable => properties,
+            _ => continue,
+        };
+
+        let pos = pos + offset;
+
+        if !properties.shapes.is_empty() {
+            for (iso, shape) in properties.shapes.iter() {
+                colliders.push(add_static_collider(
+                    world,
+                    Isometry2::new(pos.coords, 0.0) * iso,
+                    shape.clone(),
+                    Material::new(properties.restitution, properties.friction),
+                ));
+            }
+        } else if properties.shape == TileShape::Ball {
+            let radius = cell.x.min(cell.y) * 0.5 - COLLIDER_MARGIN;
+            colliders.push(add_static_collider(
+                world,
+                Isometry2::new(pos.coords, 0.0),
+                ShapeHandle::new(Ball::new(radius)),
+                Material::new(properties.restitution, properties.friction),
+            ));
+        } else {
+            let x = ((pos.x - offset.x) / cell.x + width as f32 * 0.5).round() as usize;
+            let y = ((pos.y - offset.y) / cell.y + height as f32 * 0.5).round() as usize;
+            solid[y * width + x] = Some((properties.friction, properties.restitution));
+        }
+    }
+
+    // Greedy meshing: grow each run to the right, then upwards while the rows match
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            let material = solid[y * width + x];
+            if material.is_none() {
+                x += 1;
+                continue;
+            }
+
+            let mut x_end = x + 1;
+            while x_end < width && solid[y * width + x_end] == material {
+                x_end += 1;
+            }
+
+            let mut y_end = y + 1;
+            while y_end < height && (x..x_end).all(|i| solid[y_end * width + i] == material) {
+                y_end += 1;
+            }
+
+            for row in y..y_end {
+                for i in x..x_end {
+                    solid[row * width + i] = None;
+                }
+            }
+
+            let (friction, restitution) = material.unwrap();
+            let center = Vector2::new(
+                ((x + x_end) as f32 * 0.5 - 0.5 - width as f32 * 0.5) * cell.x,
+                ((y + y_end) as f32 * 0.5 - 0.5 - height as f32 * 0.5) * cell.y,
+            ) + offset;
+            let half_extents = Vector2::new(
+                (x_end - x) as f32 * cell.x * 0.5 - COLLIDER_MARGIN,
+                (y_end - y) as f32 * cell.y * 0.5 - COLLIDER_MARGIN,
+            );
+
+            colliders.push(add_static_collider(
+                world,
+                Isometry2::new(center, 0.0),
+                ShapeHandle::new(Cuboid::new(half_extents)),
+                Material::new(restitution, friction),
+            ));
+
+            x = x_end;
+        }
+    }
+}
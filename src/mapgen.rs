@@ -0,0 +1,600 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+
+// Written next to the hand-made maps so the tileset resolves the same way
+pub const GENERATED_MAP_PATH: &str = "resources/maps/random_ranch.tmx";
+
+// Large enough that the fallback spawn ring around the ranch stays on the map
+const MAP_SIZE: u32 = 96;
+const TILE_PX: u32 = 64;
+
+// Gids in `topdown.tsx`
+const GRASS: [u32; 5] = [17, 17, 17, 18, 1];
+const DIRT: [u32; 3] = [5, 5, 6];
+const HOUSE_FLOOR: u32 = 96;
+const BARN_FLOOR: u32 = 9;
+const GREEN_TREE: [u32; 4] = [181, 182, 208, 209];
+const ORANGE_TREE: [u32; 4] = [184, 185, 211, 212];
+const BUSHES: [u32; 2] = [183, 186];
+const LEAVES: u32 = 213;
+const ROCKS: [u32; 2] = [237, 239];
+const CRATES: [u32; 2] = [129, 156];
+const BARRELS: [u32; 3] = [316, 317, 318];
+const KITCHEN: [u32; 3] = [321, 323, 297];
+const FURNITURE: [u32; 2] = [501, 503];
+const WINDOW_TOP: u32 = 464;
+const WINDOW_BOTTOM: u32 = 519;
+const WINDOW_SIDE: u32 = 437;
+
+// One wall style of the tilesheet, picked per cell from its wall neighbours
+struct WallTiles {
+    horizontal: u32,
+    vertical: u32,
+    top_left: u32,
+    top_right: u32,
+    bottom_left: u32,
+    bottom_right: u32,
+    end_left: u32,
+    end_right: u32,
+    end_top: u32,
+    end_bottom: u32,
+    tee_down: u32,
+    tee_right: u32,
+    cross: u32,
+}
+
+const WOOD_WALLS: WallTiles = WallTiles {
+    horizontal: 120,
+    vertical: 147,
+    top_left: 118,
+    top_right: 119,
+    bottom_left: 145,
+    bottom_right: 146,
+    end_left: 151,
+    end_right: 123,
+    end_top: 150,
+    end_bottom: 124,
+    tee_down: 121,
+    tee_right: 148,
+    cross: 179,
+};
+
+// Same layout as the wooden walls, six rows further down the sheet
+const STONE_WALLS: WallTiles = WallTiles {
+    horizontal: 120 + 162,
+    vertical: 147 + 162,
+    top_left: 118 + 162,
+    top_right: 119 + 162,
+    bottom_left: 145 + 162,
+    bottom_right: 146 + 162,
+    end_left: 151 + 162,
+    end_right: 123 + 162,
+    end_top: 150 + 162,
+    end_bottom: 124 + 162,
+    tee_down: 121 + 162,
+    tee_right: 148 + 162,
+    cross: 179 + 162,
+};
+
+impl WallTiles {
+    fn pick(&self, north: bool, south: bool, east: bool, west: bool) -> u32 {
+        match (north, south, east, west) {
+            (true, true, true, true) => self.cross,
+            (false, true, true, true) => self.tee_down,
+            (true, true, true, false) => self.tee_right,
+            (false, false, true, true) | (true, false, true, true) => self.horizontal,
+            (true, true, false, false) | (true, true, false, true) => self.vertical,
+            (false, true, true, false) => self.top_left,
+            (false, true, false, true) => self.top_right,
+            (true, false, true, false) => self.bottom_left,
+            (true, false, false, true) => self.bottom_right,
+            (false, false, true, false) => self.end_left,
+            (false, false, false, true) => self.end_right,
+            (false, true, false, false) => self.end_top,
+            (true, false, false, false) => self.end_bottom,
+            (false, false, false, false) => self.vertical,
+        }
+    }
+}
+
+// Inclusive tile rectangle, rows counted from the top like Tiled
+#[derive(Clone, Copy)]
+struct TileRect {
+    x0: u32,
+    y0: u32,
+    x1: u32,
+    y1: u32,
+}
+
+impl TileRect {
+    fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        TileRect {
+            x0: x,
+            y0: y,
+            x1: x + width - 1,
+            y1: y + height - 1,
+        }
+    }
+
+    // Grown on every side, clipped to the map
+    fn grow(&self, margin: u32) -> Self {
+        TileRect {
+            x0: self.x0.saturating_sub(margin),
+            y0: self.y0.saturating_sub(margin),
+            x1: (self.x1 + margin).min(MAP_SIZE - 1),
+            y1: (self.y1 + margin).min(MAP_SIZE - 1),
+        }
+    }
+
+    fn center_x(&self) -> u32 {
+        (self.x0 + self.x1) / 2
+    }
+
+    fn center_y(&self) -> u32 {
+        (self.y0 + self.y1) / 2
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (u32, u32)> {
+        let (x0, x1) = (self.x0, self.x1);
+        (self.y0..=self.y1).flat_map(move |y| (x0..=x1).map(move |x| (x, y)))
+    }
+
+    fn border(&self) -> Vec<(u32, u32)> {
+        self.cells()
+            .filter(|&(x, y)| x == self.x0 || x == self.x1 || y == self.y0 || y == self.y1)
+            .collect()
+    }
+
+    fn interior(&self) -> TileRect {
+        TileRect {
+            x0: self.x0 + 1,
+            y0: self.y0 + 1,
+            x1: self.x1 - 1,
+            y1: self.y1 - 1,
+        }
+    }
+}
+
+struct TileGrid {
+    tiles: Vec<u32>,
+}
+
+impl TileGrid {
+    fn new() -> Self {
+        TileGrid {
+            tiles: vec![0; (MAP_SIZE * MAP_SIZE) as usize],
+        }
+    }
+
+    fn get(&self, x: u32, y: u32) -> u32 {
+        self.tiles[(y * MAP_SIZE + x) as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32, gid: u32) {
+        if x < MAP_SIZE && y < MAP_SIZE {
+            self.tiles[(y * MAP_SIZE + x) as usize] = gid;
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let rows: Vec<String> = self
+            .tiles
+            .chunks(MAP_SIZE as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|gid| gid.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect();
+        rows.join(",\n")
+    }
+}
+
+struct RanchLayout {
+    rng: StdRng,
+    background: TileGrid,
+    walls: TileGrid,
+    props: TileGrid,
+    // Cells taken by buildings, roads and their surroundings; nothing else goes there
+    reserved: Vec<bool>,
+    objects: String,
+    next_object_id: u32,
+}
+
+impl RanchLayout {
+    fn new(seed: u64) -> Self {
+        RanchLayout {
+            rng: StdRng::seed_from_u64(seed),
+            background: TileGrid::new(),
+            walls: TileGrid::new(),
+            props: TileGrid::new(),
+            reserved: vec![false; (MAP_SIZE * MAP_SIZE) as usize],
+            objects: String::new(),
+            next_object_id: 1,
+        }
+    }
+
+    fn pick(&mut self, gids: &[u32]) -> u32 {
+        gids[self.rng.gen_range(0, gids.len())]
+    }
+
+    fn is_reserved(&self, x: u32, y: u32) -> bool {
+        self.reserved[(y * MAP_SIZE + x) as usize]
+    }
+
+    fn reserve(&mut self, rect: TileRect) {
+        for (x, y) in rect.cells() {
+            self.reserved[(y * MAP_SIZE + x) as usize] = true;
+        }
+    }
+
+    fn is_free(&self, rect: TileRect) -> bool {
+        rect.cells().all(|(x, y)| !self.is_reserved(x, y))
+    }
+
+    fn fill_background(&mut self, rect: TileRect, gids: &[u32]) {
+        for (x, y) in rect.cells() {
+            let gid = self.pick(gids);
+            self.background.set(x, y, gid);
+        }
+    }
+
+    fn place_walls(&mut self, cells: &[(u32, u32)], style: &WallTiles) {
+        let set: HashSet<(u32, u32)> = cells.iter().cloned().collect();
+        let has = |x: i64, y: i64| x >= 0 && y >= 0 && set.contains(&(x as u32, y as u32));
+
+        for &(x, y) in cells {
+            let (xi, yi) = (x as i64, y as i64);
+            let gid = style.pick(
+                has(xi, yi - 1),
+                has(xi, yi + 1),
+                has(xi + 1, yi),
+                has(xi - 1, yi),
+            );
+            self.walls.set(x, y, gid);
+        }
+    }
+
+    // Walled rectangle with a gap of `door` tiles in the middle of its top or bottom side
+    fn building(&mut self, rect: TileRect, style: &WallTiles, door: u32, door_on_top: bool) {
+        let door_y = if door_on_top { rect.y0 } else { rect.y1 };
+        let door_x0 = rect.center_x() + 1 - door / 2;
+        let cells: Vec<(u32, u32)> = rect
+            .border()
+            .into_iter()
+            .filter(|&(x, y)| !(y == door_y && x >= door_x0 && x < door_x0 + door))
+            .collect();
+
+        self.place_walls(&cells, style);
+    }
+
+    // Road `width` tiles wide (rounded up to odd) between two tiles
+    fn dirt_path(&mut self, from: (u32, u32), to: (u32, u32), width: u32) {
+        let (mut x, mut y) = from;
+        loop {
+            let rect = TileRect::new(x, y, 1, 1).grow(width / 2);
+            self.fill_background(rect, &DIRT);
+            self.reserve(rect);
+
+            if (x, y) == to {
+                break;
+            }
+
+            // Walk the longer axis first, wandering sideways now and then
+            let dx = to.0 as i64 - x as i64;
+            let dy = to.1 as i64 - y as i64;
+            if dy.abs() >= dx.abs() {
+                y = (y as i64 + dy.signum()) as u32;
+                if dx != 0 && self.rng.gen::<f32>() < 0.2 {
+                    x = (x as i64 + dx.signum()) as u32;
+                }
+            } else {
+                x = (x as i64 + dx.signum()) as u32;
+                if dy != 0 && self.rng.gen::<f32>() < 0.2 {
+                    y = (y as i64 + dy.signum()) as u32;
+                }
+            }
+        }
+    }
+
+    fn object(&mut self, name: &str, kind: &str, x: u32, y: u32, body: &str) {
+        let id = self.next_object_id;
+        self.next_object_id += 1;
+        write!(
+            self.objects,
+            "  <object id=\"{}\" name=\"{}\" type=\"{}\" x=\"{}\" y=\"{}\"{}",
+            id, name, kind, x, y, body
+        )
+        .unwrap();
+    }
+
+    fn point(&mut self, name: &str, kind: &str, x: u32, y: u32) {
+        self.object(name, kind, x, y, ">\n   <point/>\n  </object>\n");
+    }
+
+    fn area(&mut self, name: &str, kind: &str, rect: (u32, u32, u32, u32), inner: &str) {
+        let body = format!(
+            " width=\"{}\" height=\"{}\">\n{}  </object>\n",
+            rect.2, rect.3, inner
+        );
+        self.object(name, kind, rect.0, rect.1, &body);
+    }
+
+    // Finds a free spot for a `width` x `height` footprint away from the map edges
+    fn find_spot(&mut self, width: u32, height: u32, margin: u32) -> Option<TileRect> {
+        for _ in 0..100 {
+            let x = self.rng.gen_range(8, MAP_SIZE - 8 - width);
+            let y = self.rng.gen_range(8, MAP_SIZE - 8 - height);
+            let rect = TileRect::new(x, y, width, height);
+            if self.is_free(rect.grow(margin)) {
+                return Some(rect);
+            }
+        }
+        None
+    }
+
+    fn generate(&mut self) {
+        let all = TileRect::new(0, 0, MAP_SIZE, MAP_SIZE);
+        self.fill_background(all, &GRASS);
+
+        // Ranch house in the middle of the map, where the enemies converge
+        let house_w = self.rng.gen_range(12, 17);
+        let house_h = self.rng.gen_range(9, 13);
+        let house = TileRect::new(
+            MAP_SIZE / 2 - house_w / 2,
+            MAP_SIZE / 2 - house_h / 2,
+            house_w,
+            house_h,
+        );
+
+        self.fill_background(house.grow(3), &DIRT);
+        self.fill_background(house, &[HOUSE_FLOOR]);
+        self.building(house, &WOOD_WALLS, 2, false);
+        self.reserve(house.grow(4));
+
+        // Windows along the straight stretches of wall
+        for _ in 0..self.rng.gen_range(2, 5) {
+            let x = self.rng.gen_range(house.x0 + 2, house.x1 - 2);
+            if self.rng.gen() {
+                self.walls.set(x, house.y0, WINDOW_TOP);
+                self.walls.set(x + 1, house.y0, WINDOW_TOP);
+            } else if (x as i64 - house.center_x() as i64).abs() > 2 {
+                self.walls.set(x, house.y1, WINDOW_BOTTOM);
+                self.walls.set(x + 1, house.y1, WINDOW_BOTTOM);
+            }
+        }
+        for _ in 0..self.rng.gen_range(1, 3) {
+            let y = self.rng.gen_range(house.y0 + 2, house.y1 - 2);
+            let x = if self.rng.gen() { house.x0 } else { house.x1 };
+            self.walls.set(x, y, WINDOW_SIDE);
+            self.walls.set(x, y + 1, WINDOW_SIDE);
+        }
+
+        // Kitchen along the back wall and a couple of chairs by the door
+        let inside = house.interior();
+        let kitchen_len = self.rng.gen_range(3, 6);
+        for x in inside.x0..(inside.x0 + kitchen_len).min(inside.x1) {
+            let gid = self.pick(&KITCHEN);
+            self.walls.set(x, inside.y0, gid);
+        }
+        for x in (inside.x1 - 2)..=inside.x1 {
+            let gid = self.pick(&FURNITURE);
+            self.walls.set(x, inside.y1 - 1, gid);
+        }
+
+        // Dirt road from the front door to the south edge
+        let door_x = house.center_x();
+        self.dirt_path((door_x, house.y1 + 1), (door_x, MAP_SIZE - 1), 3);
+
+        // Stone barns with crates and barrels, linked to the road
+        for _ in 0..self.rng.gen_range(1, 3) {
+            let width = self.rng.gen_range(8, 12);
+            let height = self.rng.gen_range(6, 9);
+            if let Some(barn) = self.find_spot(width, height, 3) {
+                let door_on_top = barn.center_y() > house.center_y();
+                self.fill_background(barn, &[BARN_FLOOR]);
+                self.building(barn, &STONE_WALLS, 3, door_on_top);
+                self.reserve(barn.grow(2));
+
+                for (x, y) in barn.interior().cells() {
+                    if self.rng.gen::<f32>() < 0.15 {
+                        let gid = if self.rng.gen() {
+                            self.pick(&CRATES)
+                        } else {
+                            self.pick(&BARRELS)
+                        };
+                        self.props.set(x, y, gid);
+                    }
+                }
+
+                // Trampled yard in front of the door
+                let door_y = if door_on_top {
+                    barn.y0 - 2
+                } else {
+                    barn.y1 + 1
+                };
+                let yard = TileRect::new(barn.center_x() - 1, door_y, 4, 2);
+                self.fill_background(yard, &DIRT);
+            }
+        }
+
+        // Fenced corrals
+        for _ in 0..self.rng.gen_range(1, 4) {
+            let width = self.rng.gen_range(7, 11);
+            let height = self.rng.gen_range(5, 9);
+            if let Some(corral) = self.find_spot(width, height, 2) {
+                let gate_on_top = corral.center_y() > house.center_y();
+                self.fill_background(corral.interior(), &DIRT);
+                self.building(corral, &WOOD_WALLS, 2, gate_on_top);
+                self.reserve(corral.grow(1));
+            }
+        }
+
+        // Trees, bushes, rocks and leaves on whatever is left
+        for _ in 0..140 {
+            let x = self.rng.gen_range(1, MAP_SIZE - 2);
+            let y = self.rng.gen_range(1, MAP_SIZE - 2);
+            if !self.is_free(TileRect::new(x, y, 2, 2)) {
+                continue;
+            }
+
+            match self.rng.gen_range(0, 10) {
+                0..=3 => {
+                    let tree = if self.rng.gen() {
+                        GREEN_TREE
+                    } else {
+                        ORANGE_TREE
+                    };
+                    self.props.set(x, y, tree[0]);
+                    self.props.set(x + 1, y, tree[1]);
+                    self.props.set(x, y + 1, tree[2]);
+                    self.props.set(x + 1, y + 1, tree[3]);
+                    self.reserve(TileRect::new(x, y, 2, 2));
+                }
+                4..=7 => {
+                    let gid = self.pick(&BUSHES);
+                    self.props.set(x, y, gid);
+                    self.reserve(TileRect::new(x, y, 1, 1));
+                }
+                _ => {
+                    let gid = self.pick(&ROCKS);
+                    self.props.set(x, y, gid);
+                    self.reserve(TileRect::new(x, y, 1, 1));
+                }
+            }
+        }
+        for _ in 0..300 {
+            let x = self.rng.gen_range(0, MAP_SIZE);
+            let y = self.rng.gen_range(0, MAP_SIZE);
+            if !self.is_reserved(x, y) && self.props.get(x, y) == 0 {
+                self.props.set(x, y, LEAVES);
+            }
+        }
+
+        // Spawn and camera markers, in Tiled pixels
+        let px = |tiles: u32| tiles * TILE_PX;
+        let house_center = (
+            px(house.x0) + px(house_w) / 2,
+            px(house.y0) + px(house_h) / 2,
+        );
+
+        self.point("Player", "player_start", house_center.0, house_center.1);
+        self.point("Intro Player", "intro_player", px(door_x), px(house.y1 + 2));
+        self.point(
+            "Intro Sheriff",
+            "intro_sheriff",
+            px(door_x + 2),
+            px(house.y1 + 2),
+        );
+        self.point("SWAT Entry", "swat", px(door_x), px(MAP_SIZE - 4));
+
+        let ring = 8;
+        self.area(
+            "Dozer Ring",
+            "dozer",
+            (
+                px(ring),
+                px(ring),
+                px(MAP_SIZE - 2 * ring),
+                px(MAP_SIZE - 2 * ring),
+            ),
+            "   <ellipse/>\n",
+        );
+
+        let patrol = px(house_w.max(house_h) / 2 + 10);
+        self.area(
+            "SWAT Patrol",
+            "swat_patrol",
+            (
+                house_center.0 - patrol,
+                house_center.1 - patrol,
+                patrol * 2,
+                patrol * 2,
+            ),
+            &format!(
+                "   <properties>\n    <property name=\"inner_radius\" type=\"float\" value=\"{}\"/>\n   </properties>\n   <ellipse/>\n",
+                patrol * 3 / 4
+            ),
+        );
+
+        self.area(
+            "Ranch Hands",
+            "ally",
+            (px(MAP_SIZE - 4), house_center.1 - 256, 128, 512),
+            "",
+        );
+        self.area(
+            "Ranch House",
+            "ranch_house",
+            (px(house.x0), px(house.y0), px(house_w), px(house_h)),
+            "   <properties>\n    <property name=\"health\" type=\"float\" value=\"100\"/>\n   </properties>\n",
+        );
+    }
+
+    fn to_tmx(&self, seed: u64) -> String {
+        let mut tmx = String::new();
+        writeln!(tmx, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+        writeln!(
+            tmx,
+            "<map version=\"1.2\" tiledversion=\"2018.11.29\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{0}\" height=\"{0}\" tilewidth=\"{1}\" tileheight=\"{1}\" infinite=\"0\" nextlayerid=\"5\" nextobjectid=\"{2}\">",
+            MAP_SIZE, TILE_PX, self.next_object_id
+        )
+        .unwrap();
+        writeln!(tmx, " <properties>").unwrap();
+        writeln!(tmx, "  <property name=\"name\" value=\"Random Ranch\"/>").unwrap();
+        writeln!(
+            tmx,
+            "  <property name=\"description\" value=\"Generated from seed {}.\"/>",
+            seed
+        )
+        .unwrap();
+        writeln!(tmx, "  <property name=\"seed\" value=\"{}\"/>", seed).unwrap();
+        writeln!(tmx, " </properties>").unwrap();
+        writeln!(tmx, " <tileset firstgid=\"1\" source=\"../topdown.tsx\"/>").unwrap();
+
+        let layers = [
+            ("Background", &self.background),
+            ("Walls", &self.walls),
+            ("Props", &self.props),
+        ];
+        for (i, (name, grid)) in layers.iter().enumerate() {
+            writeln!(
+                tmx,
+                " <layer id=\"{}\" name=\"{}\" width=\"{2}\" height=\"{2}\">",
+                i + 1,
+                name,
+                MAP_SIZE
+            )
+            .unwrap();
+            writeln!(tmx, "  <data encoding=\"csv\">\n{}\n</data>", grid.to_csv()).unwrap();
+            writeln!(tmx, " </layer>").unwrap();
+        }
+
+        writeln!(tmx, " <objectgroup id=\"4\" name=\"Spawns\">").unwrap();
+        tmx.push_str(&self.objects);
+        writeln!(tmx, " </objectgroup>").unwrap();
+        writeln!(tmx, "</map>").unwrap();
+        tmx
+    }
+}
+
+// Lays out a ranch (house, barns, corrals, roads, trees and spawn zones) from `seed`
+// and returns it as a Tiled map
+pub fn generate_ranch(seed: u64) -> String {
+    let mut layout = RanchLayout::new(seed);
+    layout.generate();
+    layout.to_tmx(seed)
+}
+
+pub fn write_generated_map(path: &Path, seed: u64) {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).expect(&format!("failed to create {}", dir.display()));
+    }
+
+    std::fs::write(path, generate_ranch(seed))
+        .expect(&format!("failed to write map {}", path.display()));
+}
@@ -0,0 +1,82 @@
+use crate::GENERATED_MAP_PATH;
+use std::path::{Path, PathBuf};
+use tiled::PropertyValue;
+
+pub const MAPS_DIR: &str = "resources/maps";
+
+// Rounds with wave settings in `settings.toml`
+pub const MAX_ROUNDS: u32 = 5;
+
+#[derive(Clone)]
+pub struct MapInfo {
+    pub path: PathBuf,
+    pub name: String,
+    pub description: String,
+    pub recommended_rounds: u32,
+    // Laid out from a fresh seed whenever the map is loaded
+    pub generated: bool,
+}
+
+impl MapInfo {
+    // Metadata comes from the map's custom properties, falling back to the file name
+    pub fn from_map(path: &Path, map: &tiled::Map) -> Self {
+        let name = match map.properties.get("name") {
+            Some(PropertyValue::StringValue(name)) => name.clone(),
+            _ => path
+                .file_stem()
+                .map_or(String::new(), |stem| stem.to_string_lossy().into_owned()),
+        };
+
+        let description = match map.properties.get("description") {
+            Some(PropertyValue::StringValue(description)) => description.clone(),
+            _ => String::new(),
+        };
+
+        let recommended_rounds = match map.properties.get("recommended_rounds") {
+            Some(PropertyValue::IntValue(rounds)) => *rounds as u32,
+            _ => MAX_ROUNDS,
+        };
+
+        MapInfo {
+            path: path.to_owned(),
+            name,
+            description,
+            recommended_rounds: recommended_rounds.max(1).min(MAX_ROUNDS),
+            generated: false,
+        }
+    }
+
+    pub fn generated() -> Self {
+        MapInfo {
+            path: PathBuf::from(GENERATED_MAP_PATH),
+            name: "Random Ranch".to_owned(),
+            description: "A new ranch is laid out for every campaign.".to_owned(),
+            recommended_rounds: MAX_ROUNDS,
+            generated: true,
+        }
+    }
+
+    pub fn load_all(dir: &str) -> Vec<MapInfo> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .expect(&format!("failed to open map directory {}", dir))
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "tmx"))
+            .filter(|path| path.as_path() != Path::new(GENERATED_MAP_PATH))
+            .collect();
+        paths.sort();
+
+        let mut maps: Vec<MapInfo> = paths
+            .iter()
+            .map(|path| MapInfo::from_map(path, &load_map(path)))
+            .collect();
+
+        assert!(!maps.is_empty(), "no maps found in {}", dir);
+        maps.push(MapInfo::generated());
+        maps
+    }
+}
+
+pub fn load_map(path: &Path) -> tiled::Map {
+    tiled::parse_file(path).expect(&format!("failed to parse map {}", path.display()))
+}
@@ -0,0 +1,202 @@
+use crate::{map_px_to_world, Point2, Positional, Vector2, DOZER_OUTER_RADIUS};
+
+use rand::Rng;
+use tiled::{ObjectShape, PropertyValue};
+
+#[derive(Clone)]
+pub enum SpawnShape {
+    Point,
+    Rect { half_extents: Vector2 },
+    Ring { radii: Vector2 },
+}
+
+#[derive(Clone)]
+pub struct SpawnZone {
+    pub kind: String,
+    pub center: Point2,
+    pub shape: SpawnShape,
+}
+
+impl SpawnZone {
+    // Spawns face `ranch`
+    pub fn sample(&self, count: usize, ranch: Point2) -> Vec<Positional> {
+        let mut rng = rand::thread_rng();
+        let a_off = rng.gen::<f32>() * std::f32::consts::PI;
+
+        (0..count)
+            .map(|i| {
+                let position = match self.shape {
+                    SpawnShape::Point => self.center + Vector2::new(i as f32 * -1.5, 0.0),
+                    SpawnShape::Rect { half_extents } => {
+                        self.center
+                            + Vector2::new(
+                                half_extents.x * (rng.gen::<f32>() * 2.0 - 1.0),
+                                half_extents.y * (rng.gen::<f32>() * 2.0 - 1.0),
+                            )
+                    }
+                    SpawnShape::Ring { radii } => {
+                        // Stratified circular positioning
+                        let amin = i as f32 / count as f32;
+                        let amax = (i + 1) as f32 / count as f32;
+                        let a = a_off
+                            + (amin + (amax - amin) * rng.gen::<f32>())
+                                * std::f32::consts::PI
+                                * 2.0;
+                        self.center + Vector2::new(a.cos() * radii.x, a.sin() * radii.y)
+                    }
+                };
+
+                let to_ranch = ranch - position;
+                Positional {
+                    position,
+                    rotation: to_ranch.y.atan2(to_ranch.x),
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct PatrolRing {
+    pub center: Point2,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+}
+
+pub struct HouseMarker {
+    pub center: Point2,
+    pub half_extents: Vector2,
+    pub health: f32,
+}
+
+pub struct MapMarkers {
+    pub player_start: Point2,
+    pub intro_player: Point2,
+    pub intro_sheriff: Point2,
+    pub swat_patrol: Option<PatrolRing>,
+    pub ranch_house: Option<HouseMarker>,
+    zones: Vec<SpawnZone>,
+}
+
+impl Default for MapMarkers {
+    fn default() -> Self {
+        MapMarkers {
+            player_start: Point2::new(0.5, 0.5),
+            intro_player: Point2::new(9.120043, 3.124171),
+            intro_sheriff: Point2::new(11.19879, 3.0724447),
+            swat_patrol: None,
+            ranch_house: None,
+            zones: Vec::new(),
+        }
+    }
+}
+
+impl MapMarkers {
+    // Objects are identified by their Tiled type: `player_start`, `intro_player`,
+    // `intro_sheriff`, `swat_patrol` and `ranch_house` are markers, anything else is
+    // a spawn zone for the enemy archetype of that name.
+    pub fn from_map(map: &tiled::Map) -> Self {
+        let mut markers = MapMarkers::default();
+
+        for group in &map.object_groups {
+            for object in &group.objects {
+                let (width, height) = match object.shape {
+                    ObjectShape::Rect { width, height }
+                    | ObjectShape::Ellipse { width, height } => (width, height),
+                    _ => (0.0, 0.0),
+                };
+
+                let center = map_px_to_world(map, object.x + width * 0.5, object.y + height * 0.5);
+                let half_extents = Vector2::new(
+                    width * 0.5 / map.tile_width as f32,
+                    height * 0.5 / map.tile_width as f32,
+                );
+
+                match object.obj_type.as_str() {
+                    "" => (),
+                    "player_start" => markers.player_start = center,
+                    "intro_player" => markers.intro_player = center,
+                    "intro_sheriff" => markers.intro_sheriff = center,
+                    "swat_patrol" => {
+                        let outer_radius = half_extents.x.max(half_extents.y);
+                        let inner_radius = match object.properties.get("inner_radius") {
+                            Some(PropertyValue::FloatValue(px)) => *px / map.tile_width as f32,
+                            Some(PropertyValue::IntValue(px)) => *px as f32 / map.tile_width as f32,
+                            _ => outer_radius * 0.75,
+                        };
+
+                        markers.swat_patrol = Some(PatrolRing {
+                            center,
+                            inner_radius,
+                            outer_radius,
+                        });
+                    }
+                    "ranch_house" => {
+                        let health = match object.properties.get("health") {
+                            Some(PropertyValue::FloatValue(health)) => *health,
+                            Some(PropertyValue::IntValue(health)) => *health as f32,
+                            _ => 100.0,
+                        };
+
+                        markers.ranch_house = Some(HouseMarker {
+                            center,
+                            half_extents,
+                            health,
+                        });
+                    }
+                    kind => {
+                        let shape = match object.shape {
+                            ObjectShape::Ellipse { .. } => SpawnShape::Ring {
+                                radii: half_extents,
+                            },
+                            ObjectShape::Rect { .. } if width > 0.0 && height > 0.0 => {
+                                SpawnShape::Rect { half_extents }
+                            }
+                            _ => SpawnShape::Point,
+                        };
+
+                        markers.zones.push(SpawnZone {
+                            kind: kind.to_owned(),
+                            center,
+                            shape,
+                        });
+                    }
+                }
+            }
+        }
+
+        markers
+    }
+
+    // The house when the map has one; the world origin otherwise
+    pub fn ranch_center(&self) -> Point2 {
+        self.ranch_house
+            .as_ref()
+            .map_or(Point2::origin(), |house| house.center)
+    }
+
+    pub fn zones(&self) -> &[SpawnZone] {
+        &self.zones
+    }
+
+    // Picks one of the map's zones for this enemy kind; maps without one fall back to
+    // the classic ring around the ranch.
+    pub fn spawn_positions(&self, kind: &str, count: usize) -> Vec<Positional> {
+        let zones: Vec<&SpawnZone> = self.zones.iter().filter(|z| z.kind == kind).collect();
+
+        let fallback = SpawnZone {
+            kind: kind.to_owned(),
+            center: self.ranch_center(),
+            shape: SpawnShape::Ring {
+                radii: Vector2::new(DOZER_OUTER_RADIUS, DOZER_OUTER_RADIUS),
+            },
+        };
+
+        let zone = if zones.is_empty() {
+            &fallback
+        } else {
+            zones[rand::thread_rng().gen_range(0, zones.len())]
+        };
+
+        zone.sample(count, self.ranch_center())
+    }
+}
@@ -1,12 +1,15 @@
 use crate::{
-    graphics, Color, Context, KeyCode, MouseButton, MusicTrack, PlayerInput, Point2, Settings,
-    Vector2, WorldData,
+    draw_shadowed_text, graphics, Color, Context, KeyCode, MouseButton, MusicTrack, PlayerInput,
+    Point2, Settings, Vector2, WorldData,
 };
 
 pub struct MenuPhase {
     pub first_update: bool,
     pub start_game: bool,
+    pub open_editor: bool,
     pub music_track: MusicTrack,
+    // Map picked on the select screen, loaded when the game starts
+    pub map_index: usize,
 }
 
 impl MenuPhase {
@@ -14,13 +17,16 @@ impl MenuPhase {
         MenuPhase {
             first_update: true,
             start_game: false,
+            open_editor: false,
             music_track: MusicTrack::new("twisted", ctx),
+            map_index: 0,
         }
     }
 
     pub fn update(&mut self, settings: &Settings, data: &mut WorldData, _ctx: &mut Context) {
         if self.first_update {
             data.player_input = PlayerInput::default();
+            self.map_index = data.map_index;
             self.first_update = false;
         }
 
@@ -74,18 +80,76 @@ impl MenuPhase {
                 .color(Color::from((255, 255, 255, 255))),
         )
         .unwrap();
+
+        self.draw_map_select(data, ctx, window_size.1 as f32 - text_height - 40.0);
+    }
+
+    fn draw_map_select(&self, data: &WorldData, ctx: &mut Context, bottom: f32) {
+        let window_size = graphics::drawable_size(ctx);
+        let info = &data.maps[self.map_index];
+
+        let mut lines = vec![
+            (
+                graphics::Text::new((info.name.as_str(), data.font, 64.0)),
+                Color::from((255, 255, 255, 255)),
+            ),
+            (
+                graphics::Text::new((
+                    format!("{}  ({} rounds)", info.description, info.recommended_rounds),
+                    data.font,
+                    32.0,
+                )),
+                Color::from((255, 220, 160, 255)),
+            ),
+        ];
+
+        if data.maps.len() > 1 {
+            lines.push((
+                graphics::Text::new((
+                    format!(
+                        "< Left / Right to choose a map ({} of {}) >",
+                        self.map_index + 1,
+                        data.maps.len()
+                    ),
+                    data.font,
+                    32.0,
+                )),
+                Color::from((200, 200, 200, 255)),
+            ));
+        }
+
+        lines.push((
+            graphics::Text::new(("E to edit this map", data.font, 32.0)),
+            Color::from((200, 200, 200, 255)),
+        ));
+
+        let mut y = bottom;
+        for (text, color) in lines.iter().rev() {
+            y -= text.height(ctx) as f32 + 8.0;
+            let x = (window_size.0 as f32 - text.width(ctx) as f32) / 2.0;
+            draw_shadowed_text(ctx, Point2::new(x, y), text, *color);
+        }
     }
 
     pub fn handle_key(
         &mut self,
         _settings: &Settings,
-        _data: &mut WorldData,
+        data: &mut WorldData,
         _ctx: &mut Context,
         key_code: KeyCode,
         value: bool,
     ) {
-        if key_code == KeyCode::Space && value {
-            self.start_game = true;
+        if !value {
+            return;
+        }
+
+        let map_count = data.maps.len();
+        match key_code {
+            KeyCode::Space => self.start_game = true,
+            KeyCode::E => self.open_editor = true,
+            KeyCode::Left => self.map_index = (self.map_index + map_count - 1) % map_count,
+            KeyCode::Right => self.map_index = (self.map_index + 1) % map_count,
+            _ => (),
         }
     }
 
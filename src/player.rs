@@ -1,8 +1,8 @@
 use super::types::*;
 use crate::{
     graphics::spritebatch::SpriteBatch, graphics::DrawParam, Ball, BodyHandle, Bullet, Characters,
-    Color, Force2, Isometry2, Material, Point2, Positional, Rect, ShapeHandle, Vector2, Volumetric,
-    Weapon, World,
+    Color, Faction, Force2, Isometry2, Material, Point2, Positional, Rect, ShapeHandle,
+    StatusEffects, Vector2, Volumetric, Weapon, World,
 };
 use nalgebra as na;
 use ncollide2d::world::CollisionGroups;
@@ -37,11 +37,35 @@ impl Default for PawnInput {
     }
 }
 
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct PawnMovement {
+    pub max_force: f32,
+    pub force_rate: f32,
+    pub max_vel: f32,
+}
+
+impl Default for PawnMovement {
+    fn default() -> Self {
+        Self {
+            max_force: 10.0,
+            force_rate: 0.8,
+            max_vel: 7.0,
+        }
+    }
+}
+
 pub struct Player {
     pub weapon: Weapon,
+    // The original weapon, put away while a looted one has ammo left
+    holstered: Option<Weapon>,
     pub health: f32,
+    max_health: f32,
     input: PawnInput,
-    group: usize,
+    limits: PawnMovement,
+    speed_scale: f32,
+    pub status: StatusEffects,
+    faction: Faction,
     pub body_handle: BodyHandle,
     pub visual: VisualState,
     pub spritebatch: Rc<RefCell<SpriteBatch>>,
@@ -102,20 +126,46 @@ impl Player {
         health: f32,
         weapon: Weapon,
         pos: Point2,
-        group: usize,
+        faction: Faction,
+        characters: &Characters,
+        spritebatch: Rc<RefCell<SpriteBatch>>,
+    ) -> Self {
+        let rb = add_player_rigid_body(world, pos, faction.group());
+        Self::from_rigid_body(
+            rb,
+            name,
+            health,
+            weapon,
+            pos,
+            faction,
+            characters,
+            spritebatch,
+        )
+    }
+
+    pub fn from_rigid_body(
+        rb: BodyHandle,
+        name: &str,
+        health: f32,
+        weapon: Weapon,
+        pos: Point2,
+        faction: Faction,
         characters: &Characters,
         spritebatch: Rc<RefCell<SpriteBatch>>,
     ) -> Self {
         let entry = characters.get_entry(name);
         let zombie = characters.get_entry("zombie");
 
-        let rb = add_player_rigid_body(world, pos, group);
-
         Player {
             weapon,
+            holstered: None,
             health,
-            group,
+            max_health: health,
+            faction,
             input: PawnInput::default(),
+            limits: PawnMovement::default(),
+            speed_scale: 1.0,
+            status: StatusEffects::default(),
             body_handle: rb,
             visual: VisualState::Stand,
             spritebatch,
@@ -139,14 +189,37 @@ impl Player {
         }
     }
 
+    pub fn faction(&self) -> Faction {
+        self.faction
+    }
+
+    pub fn set_movement(&mut self, limits: PawnMovement) {
+        self.limits = limits;
+    }
+
+    pub fn set_speed_scale(&mut self, speed_scale: f32) {
+        self.speed_scale = speed_scale;
+    }
+
     pub fn set_input(&mut self, input: PawnInput) {
         self.input = input;
     }
 
+    pub fn equip(&mut self, weapon: Weapon) {
+        let previous = std::mem::replace(&mut self.weapon, weapon);
+        if self.holstered.is_none() {
+            self.holstered = Some(previous);
+        }
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
+
     fn color(&self) -> Color {
         let t = self.time_since_last_damage;
         let t = (1.0 - t * 5.0).max(0.0) * 10.0;
-        Color::new(1.0 + t, 1.0 + t, 1.0 + t, 1.0)
+        self.status.tint(Color::new(1.0 + t, 1.0 + t, 1.0 + t, 1.0))
     }
 
     pub fn draw(&self) {
@@ -212,6 +285,10 @@ impl Player {
         self.health
     }
 
+    pub fn max_health(&self) -> f32 {
+        self.max_health
+    }
+
     pub fn damage(&mut self, amount: f32) {
         self.health -= amount.min(self.health);
         self.time_since_last_damage = 0.0;
@@ -230,26 +307,28 @@ impl Player {
         let pos = rigid_body.position();
         self.positional.position = pos.translation.vector.into();
 
+        let dt = 1.0 / 60.0;
+        let status_damage = self.status.update(dt);
+        self.health -= status_damage.min(self.health);
+        let stunned = self.status.stunned();
+
         let aim_rel = self.input.aim_pos - self.positional.position;
-        if aim_rel.x != 0.0 || aim_rel.y != 0.0 {
+        if !stunned && (aim_rel.x != 0.0 || aim_rel.y != 0.0) {
             self.positional.rotation = (aim_rel.y).atan2(aim_rel.x);
         };
 
         let velocity = rigid_body.velocity().linear;
-
-        const MAX_FORCE: f32 = 10.0;
-        const FORCE_RATE: f32 = 0.8;
-        const MAX_VEL: f32 = 7.0;
+        let limits = &self.limits;
 
         let mut target_vel = clamp_norm(
             Vector2::new(self.input.movement.right, self.input.movement.forward),
             1.0,
         );
 
-        target_vel *= MAX_VEL;
+        target_vel *= limits.max_vel * self.speed_scale * self.status.speed_scale();
         target_vel -= velocity;
 
-        let force = clamp_norm(target_vel * FORCE_RATE, MAX_FORCE);
+        let force = clamp_norm(target_vel * limits.force_rate, limits.max_force);
 
         rigid_body.activate();
         rigid_body.apply_force(&Force2::new(force, 0.0));
@@ -257,10 +336,20 @@ impl Player {
         pos.rotation = nalgebra::UnitComplex::from_angle(0.0);
         rigid_body.set_position(pos);
 
-        self.weapon
-            .update(self.input.shoot, &self.positional, self.group, bullets_out);
+        self.weapon.update(
+            self.input.shoot && !stunned,
+            &self.positional,
+            self.faction,
+            bullets_out,
+        );
+
+        if self.weapon.out_of_ammo() {
+            if let Some(weapon) = self.holstered.take() {
+                self.weapon = weapon;
+            }
+        }
 
-        self.time_since_last_damage += 1.0 / 60.0;
+        self.time_since_last_damage += dt;
     }
 }
 
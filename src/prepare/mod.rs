@@ -1,11 +1,9 @@
 use crate::{
-    draw_map_layer, draw_shadowed_text, graphics, px_to_world, tile_id_to_src_rect, BodyHandle,
-    CollisionGroups, Color, Context, Cuboid, Isometry2, KeyCode, MainState, Material, Matrix4,
-    MouseButton, PlayerInput, Point2, Positional, RoundData, Settings, ShapeHandle, Spring,
-    Vector2, Vector3, VisualState, Volumetric, WallPiece, WorldData, COLLIDER_MARGIN, GROUP_WORLD,
+    draw_map_layer, draw_shadowed_text, graphics, px_to_world, Color, Context, KeyCode, MainState,
+    Matrix4, MouseButton, PlayerInput, Point2, Positional, RoundData, Settings, Vector2, Vector3,
+    VisualState, WorldData,
 };
 
-use nalgebra as na;
 use rand::{thread_rng, Rng};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -19,6 +17,8 @@ pub struct PreparePhase {
     pub round_data: Rc<RefCell<RoundData>>,
     pub crate_supplies: u32,
     pub rock_supplies: u32,
+    // Extra crates built from scrap salvaged last round
+    pub salvaged_crates: u32,
     pub voice_played: bool,
     pub play_voice_at: Instant,
 }
@@ -38,6 +38,7 @@ impl PreparePhase {
             round_data,
             crate_supplies: 0,
             rock_supplies: 0,
+            salvaged_crates: 0,
             voice_played: false,
             play_voice_at: Instant::now() + Duration::from_millis(1500),
         }
@@ -56,7 +57,10 @@ impl PreparePhase {
                 4 => (settings.round5_crates, settings.round5_rocks),
                 _ => unimplemented!(),
             };
-            self.crate_supplies = crate_count;
+            self.salvaged_crates = data.scrap;
+            data.scrap = 0;
+
+            self.crate_supplies = crate_count + self.salvaged_crates;
             self.rock_supplies = rock_count;
             self.first_update = false;
         }
@@ -103,33 +107,29 @@ impl PreparePhase {
 
         graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Background",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Background",
+            data.screen_to_world,
+        );
 
-        {
-            MainState::draw_wall_pieces(&data.wall_pieces, &data.world, &mut data.map_spritebatch);
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        MainState::draw_wall_pieces(
+            &data.wall_pieces,
+            &data.world,
+            &mut data.map_graphics,
+            &data.map,
+        );
+        data.map_graphics.draw_and_clear(ctx);
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Props",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Props",
+            data.screen_to_world,
+        );
 
         data.player.draw();
 
@@ -143,8 +143,18 @@ impl PreparePhase {
         graphics::set_transform(ctx, identity_transform);
         graphics::apply_transformations(ctx).unwrap();
 
-        let crates_text =
-            graphics::Text::new((format!("Crates: {}", self.crate_supplies), data.font, 64.0));
+        let crates_text = if self.salvaged_crates > 0 {
+            graphics::Text::new((
+                format!(
+                    "Crates: {}  (+{} from scrap)",
+                    self.crate_supplies, self.salvaged_crates
+                ),
+                data.font,
+                64.0,
+            ))
+        } else {
+            graphics::Text::new((format!("Crates: {}", self.crate_supplies), data.font, 64.0))
+        };
         let rocks_text =
             graphics::Text::new((format!("Rocks: {}", self.rock_supplies), data.font, 64.0));
 
@@ -323,113 +333,20 @@ impl PreparePhase {
     }
 
     pub fn place_rock(&mut self, pos: Point2, data: &mut WorldData, _ctx: &mut Context) {
-        //let view = TileMapLayerView::new(get_map_layer(&data.map, "Props"));
-
         data.sounds.play_break2();
 
+        // Rocks are stone in the tileset, so they outlast crates
         let mut rng = thread_rng();
-        let tile_id = 236 + rng.gen_range(0, 3);
-        let src = tile_id_to_src_rect(tile_id, &data.map, &data.map_tile_image);
-
-        let rb = {
-            let rad = 0.5 - COLLIDER_MARGIN;
-
-            // Sim as balls for less coupling between elements
-            //let geom = ShapeHandle::new(Ball::new(rad));
-            let geom = ShapeHandle::new(Cuboid::new(Vector2::new(rad, rad)));
-
-            let inertia = geom.inertia(10.0);
-            let center_of_mass = geom.center_of_mass();
-
-            let pos = Isometry2::new(Vector2::new(pos.x, pos.y), na::zero());
-            let rb = data.world.add_rigid_body(pos, inertia, center_of_mass);
-
-            let collider_handle = data.world.add_collider(
-                COLLIDER_MARGIN,
-                geom.clone(),
-                rb,
-                Isometry2::identity(),
-                Material::new(0.3, 0.0),
-            );
-
-            let mut col_group = CollisionGroups::new();
-            col_group.set_membership(&[GROUP_WORLD]);
-            data.world
-                .collision_world_mut()
-                .set_collision_groups(collider_handle, col_group);
-
-            rb
-        };
-
-        let spring = data.world.add_force_generator(Spring::new(
-            BodyHandle::ground(),
-            rb,
-            pos,
-            Point2::origin(),
-            0.0,
-            100.0,
-        ));
-
-        data.wall_pieces.push(WallPiece {
-            tile_snip: src,
-            rb,
-            spring,
-            hp: 1.0,
-        });
+        let tile = data
+            .map_graphics
+            .first_tileset_gid(236 + rng.gen_range(0, 3));
+        data.add_wall_piece(tile, pos);
     }
 
     pub fn place_crate(&mut self, pos: Point2, data: &mut WorldData, _ctx: &mut Context) {
-        //let view = TileMapLayerView::new(get_map_layer(&data.map, "Props"));
-
         data.sounds.play_break1();
 
-        let tile_id = 128;
-        let src = tile_id_to_src_rect(tile_id, &data.map, &data.map_tile_image);
-
-        let rb = {
-            let rad = 0.5 - COLLIDER_MARGIN;
-
-            // Sim as balls for less coupling between elements
-            //let geom = ShapeHandle::new(Ball::new(rad));
-            let geom = ShapeHandle::new(Cuboid::new(Vector2::new(rad, rad)));
-
-            let inertia = geom.inertia(10.0);
-            let center_of_mass = geom.center_of_mass();
-
-            let pos = Isometry2::new(Vector2::new(pos.x, pos.y), na::zero());
-            let rb = data.world.add_rigid_body(pos, inertia, center_of_mass);
-
-            let collider_handle = data.world.add_collider(
-                COLLIDER_MARGIN,
-                geom.clone(),
-                rb,
-                Isometry2::identity(),
-                Material::new(0.3, 0.0),
-            );
-
-            let mut col_group = CollisionGroups::new();
-            col_group.set_membership(&[GROUP_WORLD]);
-            data.world
-                .collision_world_mut()
-                .set_collision_groups(collider_handle, col_group);
-
-            rb
-        };
-
-        let spring = data.world.add_force_generator(Spring::new(
-            BodyHandle::ground(),
-            rb,
-            pos,
-            Point2::origin(),
-            0.0,
-            100.0,
-        ));
-
-        data.wall_pieces.push(WallPiece {
-            tile_snip: src,
-            rb,
-            spring,
-            hp: 1.0,
-        });
+        let tile = data.map_graphics.first_tileset_gid(128);
+        data.add_wall_piece(tile, pos);
     }
 }
@@ -0,0 +1,164 @@
+use super::super::consts::*;
+use crate::{
+    draw_shadowed_text, graphics, world_to_px, Ball, Color, Context, Cuboid, Isometry2, Point2,
+    ShapeHandle, WorldData,
+};
+use ncollide2d::shape::{Compound, ConvexPolygon};
+
+const LINE_WIDTH: f32 = 0.05;
+
+fn draw_line(ctx: &mut Context, points: &[Point2], color: Color) {
+    // Degenerate lines upset the tessellator
+    if points.len() < 2 || (points[1] - points[0]).norm() < 1e-3 {
+        return;
+    }
+
+    let mesh = graphics::Mesh::new_line(ctx, points, LINE_WIDTH, color).unwrap();
+    graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+}
+
+fn draw_marker(ctx: &mut Context, pos: Point2, color: Color) {
+    let mesh = graphics::Mesh::new_circle(
+        ctx,
+        graphics::DrawMode::stroke(LINE_WIDTH),
+        pos,
+        0.3,
+        0.05,
+        color,
+    )
+    .unwrap();
+    graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+}
+
+// Outlines of every collider in the physics world, colored by collision group
+fn draw_colliders(data: &WorldData, ctx: &mut Context) {
+    let groups = [
+        (GROUP_AIR, Color::new(0.6, 0.6, 1.0, 0.8)),
+        (GROUP_PROJECTILE, Color::new(1.0, 1.0, 0.0, 0.8)),
+        (GROUP_ENEMY, Color::new(1.0, 0.3, 0.3, 0.8)),
+        (GROUP_PLAYER, Color::new(0.3, 1.0, 0.3, 0.8)),
+        (GROUP_ALLY, Color::new(0.3, 0.8, 1.0, 0.8)),
+        (GROUP_LIVESTOCK, Color::new(0.9, 0.7, 0.4, 0.8)),
+    ];
+
+    for collider in data.world.collision_world().collision_objects() {
+        let color = groups
+            .iter()
+            .find(|(group, _)| collider.collision_groups().is_member_of(*group))
+            .map_or(Color::new(1.0, 1.0, 1.0, 0.5), |(_, color)| *color);
+
+        draw_shape(ctx, collider.shape(), collider.position(), color);
+    }
+}
+
+fn draw_shape(ctx: &mut Context, shape: &ShapeHandle<f32>, iso: &Isometry2<f32>, color: Color) {
+    if let Some(ball) = shape.as_shape::<Ball<f32>>() {
+        let mesh = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(LINE_WIDTH),
+            Point2::from(iso.translation.vector),
+            ball.radius(),
+            0.05,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    } else if let Some(cuboid) = shape.as_shape::<Cuboid<f32>>() {
+        let e = cuboid.half_extents();
+        let corners: Vec<Point2> = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .iter()
+            .map(|(x, y)| iso * Point2::new(e.x * x, e.y * y))
+            .collect();
+
+        let mesh = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::stroke(LINE_WIDTH),
+            &corners,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    } else if let Some(polygon) = shape.as_shape::<ConvexPolygon<f32>>() {
+        let corners: Vec<Point2> = polygon.points().iter().map(|p| iso * p).collect();
+
+        let mesh = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::stroke(LINE_WIDTH),
+            &corners,
+            color,
+        )
+        .unwrap();
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+    } else if let Some(compound) = shape.as_shape::<Compound<f32>>() {
+        // Wall tiles with several collision objects drawn in Tiled
+        for (part_iso, part) in compound.shapes() {
+            draw_shape(ctx, part, &(iso * part_iso), color);
+        }
+    }
+}
+
+// Drawn in world space: colliders, velocities, line of sight, targets and paths
+pub fn draw_ai_debug(data: &WorldData, ctx: &mut Context) {
+    draw_colliders(data, ctx);
+
+    for enemy in &data.enemies {
+        let pos = enemy.positional().position;
+        let debug = enemy.ai_debug();
+
+        if let Some(body) = enemy.rigid_body().and_then(|h| data.world.rigid_body(h)) {
+            let vel = body.velocity().linear;
+            draw_line(ctx, &[pos, pos + vel * 0.5], Color::new(0.0, 1.0, 1.0, 1.0));
+        }
+
+        if let Some((from, to, visible)) = debug.sight {
+            let color = if visible {
+                Color::new(0.0, 1.0, 0.0, 0.8)
+            } else {
+                Color::new(1.0, 0.0, 0.0, 0.4)
+            };
+            draw_line(ctx, &[from, to], color);
+        }
+
+        for segment in debug.path.windows(2) {
+            draw_line(ctx, segment, Color::new(1.0, 1.0, 1.0, 0.6));
+        }
+
+        if let Some(target) = debug.target {
+            draw_line(ctx, &[pos, target], Color::new(1.0, 0.5, 0.0, 0.4));
+            draw_marker(ctx, target, Color::new(1.0, 0.5, 0.0, 1.0));
+        }
+    }
+}
+
+// Drawn in screen space: each enemy's current state next to it
+pub fn draw_ai_debug_labels(data: &WorldData, ctx: &mut Context) {
+    for enemy in &data.enemies {
+        let debug = enemy.ai_debug();
+        if debug.state.is_empty() {
+            continue;
+        }
+
+        let wall_damage = enemy
+            .rigid_body()
+            .and_then(|body| data.wall_damage_dealt.get(&body).cloned())
+            .unwrap_or(0.0);
+
+        let pos = world_to_px(data.world_to_screen, enemy.positional().position);
+        let text = graphics::Text::new((
+            format!(
+                "{} morale {:.2} walls {:.1}",
+                debug.state,
+                enemy.morale(),
+                wall_damage
+            ),
+            data.font,
+            24.0,
+        ));
+        draw_shadowed_text(
+            ctx,
+            Point2::new(pos.x + 20.0, pos.y - 20.0),
+            &text,
+            Color::from((255, 255, 0, 255)),
+        );
+    }
+}
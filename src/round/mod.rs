@@ -1,24 +1,45 @@
 use super::consts::*;
-use super::enemy::Swat;
 use crate::{
-    draw_map_layer, draw_shadowed_text, graphics, px_to_world, settings::Settings, Bulldozer,
-    Color, Context, Enemy, EnemyDozerBehavior, KeyCode, MainState, Matrix4, MouseButton, Player,
-    PlayerInput, Point2, Positional, RoundData, Vector2, Vector3, VisualState, Weapon,
-    WeaponConfig, WorldData, DESIRED_FPS,
+    draw_map_layer, draw_shadowed_text, graphics, map_bounds, px_to_world, reachable_cells,
+    settings::Settings, solid_prop_cells, spawn_enemy, world_to_map_cell, Ally, BodyHandle, Color,
+    Context, Detonation, EnemyRequest, Faction, GasCloud, Grenade, KeyCode, LootKind, MainState,
+    Matrix4, MouseButton, PawnInput, Pickup, PlayerInput, Point2, Positional, Rect, RoundData,
+    Vector2, Vector3, VisualState, WaveConfig, Weapon, WeaponConfig, WorldData, AMMO_PICKUP_ROUNDS,
+    DESIRED_FPS, LOOTED_SMG, MEDKIT_HEALTH, PICKUP_RADIUS, RANCH_HANDS,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use na::Isometry2;
-use nalgebra as na;
-use ncollide2d::query::Ray;
-use ncollide2d::shape::{Cuboid, ShapeHandle};
-use ncollide2d::world::CollisionGroups;
-use nphysics2d::object::Material;
-use nphysics2d::volumetric::Volumetric;
-use nphysics2d::world::World;
+mod debug;
+use self::debug::*;
 
-use ggez::audio;
+use ncollide2d::query::{Proximity, Ray};
+
+// How long before a timed wave arrives the reinforcements are announced
+const WAVE_ANNOUNCE_SECONDS: f32 = 5.0;
+
+// Morale lost by enemies near a fallen comrade, and by enemies within earshot of a taunt
+const MORALE_DEATH_RADIUS: f32 = 15.0;
+const MORALE_DEATH_SHOCK: f32 = 0.25;
+const MORALE_TAUNT_RADIUS: f32 = 25.0;
+const MORALE_TAUNT_SHOCK: f32 = 0.1;
+const MORALE_TAUNT_COOLDOWN_SECONDS: f32 = 3.0;
+
+const NOTICE_SECONDS: f32 = 2.0;
+
+// Distance from the map edge at which a fleeing enemy counts as gone
+const ESCAPE_MARGIN: f32 = 3.0;
+
+// How far from a fallen pawn its drops land
+const LOOT_SPREAD: f32 = 0.9;
+
+// How close to a gap in the house walls an enemy has to be before making for the house itself
+const BREACH_ENTER_DISTANCE: f32 = 2.0;
+
+struct PendingWave {
+    cfg: WaveConfig,
+    announced: bool,
+}
 
 pub struct RoundPhase {
     pub first_update: bool,
@@ -27,10 +48,28 @@ pub struct RoundPhase {
     pub victory: bool,
     pub failure: bool,
     pub round_data: Rc<RefCell<RoundData>>,
+    elapsed_seconds: f32,
+    pending_waves: Vec<PendingWave>,
+    initial_wall_count: usize,
+    // Wall pieces along the house outline, and where each of them stood
+    house_walls: Vec<(BodyHandle, Point2)>,
+    solid_cells: Vec<Vec<bool>>,
+    // Gaps knocked in the house walls, and the map cells from which they can be reached.
+    // Rebuilt whenever the number of wall pieces changes.
+    breaches: Vec<Point2>,
+    breach_cells: Vec<Vec<bool>>,
+    breach_wall_count: usize,
+    radio_charges: u32,
+    radio_allies: u32,
+    last_taunt_seconds: f32,
+    routed_count: u32,
+    debug_overlay: bool,
+    notice: Option<(String, f32)>,
 }
 
 enum BulletHitVictim {
-    Enemy(usize),
+    Enemy(usize, Point2),
+    Ally(usize),
     Player,
     None,
 }
@@ -49,29 +88,26 @@ impl RoundPhase {
             victory: false,
             failure: false,
             round_data,
+            elapsed_seconds: 0.0,
+            pending_waves: Vec::new(),
+            initial_wall_count: 0,
+            house_walls: Vec::new(),
+            solid_cells: Vec::new(),
+            breaches: Vec::new(),
+            breach_cells: Vec::new(),
+            breach_wall_count: 0,
+            radio_charges: 0,
+            radio_allies: 0,
+            last_taunt_seconds: -MORALE_TAUNT_COOLDOWN_SECONDS,
+            routed_count: 0,
+            debug_overlay: false,
+            notice: None,
         }
     }
 
     fn spawn_bulldozers(&mut self, data: &mut WorldData, ctx: &mut Context, count: usize) {
-        let a_off = rand::random::<f32>() * std::f32::consts::PI;
-
-        // Stratified circular positioning
-        for i in 0..count {
-            let amin = i as f32 / count as f32;
-            let amax = (i + 1) as f32 / count as f32;
-            let a =
-                a_off + (amin + (amax - amin) * rand::random::<f32>()) * std::f32::consts::PI * 2.0;
-
-            const SPAWN_DIST: f32 = DOZER_OUTER_RADIUS;
-
-            let dozer = spawn_dozer(
-                ctx,
-                &mut data.world,
-                data.engine_data.clone(),
-                data.dozer_image.clone(),
-                Point2::new(a.cos() * SPAWN_DIST, a.sin() * SPAWN_DIST),
-                std::f32::consts::PI + a,
-            );
+        for spawn in data.markers.spawn_positions("dozer", count) {
+            let dozer = spawn_enemy(ctx, data, "dozer", spawn.position, spawn.rotation);
             data.enemies.push(dozer);
         }
     }
@@ -81,18 +117,55 @@ impl RoundPhase {
             data.sounds.play_swat_gogogo();
         }
 
-        for i in 0..count {
-            let swat_pawn = Player::new(
+        for spawn in data.markers.spawn_positions("swat", count) {
+            let swat = spawn_enemy(ctx, data, "swat", spawn.position, spawn.rotation);
+            data.enemies.push(swat);
+        }
+    }
+
+    fn spawn_bosses(&mut self, data: &mut WorldData, ctx: &mut Context, count: usize) {
+        for spawn in data.markers.spawn_positions("boss", count) {
+            let boss = spawn_enemy(ctx, data, "boss", spawn.position, spawn.rotation);
+            data.enemies.push(boss);
+        }
+    }
+
+    fn spawn_archetype(
+        &mut self,
+        data: &mut WorldData,
+        ctx: &mut Context,
+        name: &str,
+        count: usize,
+    ) {
+        for spawn in data.markers.spawn_positions(name, count) {
+            let enemy = spawn_enemy(ctx, data, name, spawn.position, spawn.rotation);
+            data.enemies.push(enemy);
+        }
+    }
+
+    fn call_radio(&mut self, data: &mut WorldData, ctx: &mut Context) {
+        if self.radio_charges == 0 || !data.player.alive() {
+            return;
+        }
+
+        self.radio_charges -= 1;
+        data.sounds.play_radio();
+
+        let spawns = data
+            .markers
+            .spawn_positions("ally", self.radio_allies as usize);
+        for (i, spawn) in spawns.into_iter().enumerate() {
+            let (character, weapon) = RANCH_HANDS[i % RANCH_HANDS.len()];
+            let ally = Ally::new(
+                ctx,
                 &mut data.world,
-                "soldier",
-                0.5,
-                Weapon::from_config(ctx, WeaponConfig::from_toml("resources/swat_smg.toml")),
-                Point2::new(-30.0 + (i as f32 * -1.5), 10.0),
-                GROUP_ENEMY,
+                character,
+                weapon,
+                spawn.position,
                 &data.characters,
                 data.character_spritebatch.clone(),
             );
-            data.enemies.push(Box::new(Swat::new(swat_pawn)));
+            data.allies.push(ally);
         }
     }
 
@@ -102,42 +175,115 @@ impl RoundPhase {
             data.player.set_visual(VisualState::Gun);
 
             if settings.enemies {
-                let (dozer_count, swat_count) = match self.round_index {
-                    0 => (settings.round1_dozers, settings.round1_swat),
-                    1 => (settings.round2_dozers, settings.round2_swat),
-                    2 => (settings.round3_dozers, settings.round3_swat),
-                    3 => (settings.round4_dozers, settings.round4_swat),
-                    4 => (settings.round5_dozers, settings.round5_swat),
+                let (dozer_count, swat_count, waves) = match self.round_index {
+                    0 => (
+                        settings.round1_dozers,
+                        settings.round1_swat,
+                        &settings.round1_waves,
+                    ),
+                    1 => (
+                        settings.round2_dozers,
+                        settings.round2_swat,
+                        &settings.round2_waves,
+                    ),
+                    2 => (
+                        settings.round3_dozers,
+                        settings.round3_swat,
+                        &settings.round3_waves,
+                    ),
+                    3 => (
+                        settings.round4_dozers,
+                        settings.round4_swat,
+                        &settings.round4_waves,
+                    ),
+                    4 => (
+                        settings.round5_dozers,
+                        settings.round5_swat,
+                        &settings.round5_waves,
+                    ),
                     _ => unimplemented!(),
                 };
 
                 self.spawn_bulldozers(data, ctx, dozer_count as usize);
                 self.spawn_swat(data, ctx, swat_count as usize);
+
+                self.pending_waves = waves
+                    .iter()
+                    .map(|cfg| PendingWave {
+                        cfg: cfg.clone(),
+                        announced: false,
+                    })
+                    .collect();
             }
 
+            self.initial_wall_count = data.wall_pieces.len();
+            if let Some(ref house) = data.house {
+                self.house_walls = data
+                    .wall_pieces
+                    .iter()
+                    .filter(|wp| house.on_perimeter(wp.anchor))
+                    .map(|wp| (wp.rb, wp.anchor))
+                    .collect();
+            }
+            self.solid_cells = solid_prop_cells(&data.map, &data.tile_catalog);
+            self.breach_wall_count = data.wall_pieces.len();
+            self.radio_charges = settings.radio_charges;
+            self.radio_allies = settings.radio_allies;
             self.first_update = false;
         }
 
+        self.elapsed_seconds += 1.0 / DESIRED_FPS as f32;
+
         let round_data = self.round_data.clone();
         let mut round_data = round_data.borrow_mut();
         if settings.music && !round_data.music_track.playing() {
             round_data.music_track.play();
         }
 
+        // Streaming eyes make the view swim
+        let t = self.elapsed_seconds;
+        let swim = Vector2::new((t * 1.7).sin(), (t * 1.3).cos()) * (data.gas_exposure * 0.6);
+
         self.calculate_view_transform(
             data,
             &ctx,
-            data.camera_pos,
+            data.camera_pos + swim,
             if data.strategic_view { 0.02 } else { 0.1 },
         );
 
-        data.player.set_input((&data.player_input).into());
+        let mut player_input: PawnInput = (&data.player_input).into();
+        if data.flash_seconds > 0.0 {
+            // Flashbanged players can't hold their aim steady
+            let t = self.elapsed_seconds;
+            let sway = data.flash_seconds.min(1.0) * 4.0;
+            player_input.aim_pos += Vector2::new((t * 3.1).sin(), (t * 2.3).cos()) * sway;
+        }
+        data.player.set_input(player_input);
         data.player.update(&mut data.world, &mut data.bullets);
 
-        for (_i, enemy) in &mut data.enemies.iter_mut().enumerate() {
+        // Anyone under a spotlight is marked for every unit
+        let player_pos = data.player.positional.position;
+        let player_spotted = data.enemies.iter().any(|enemy| {
+            enemy
+                .spotlight()
+                .map_or(false, |(pos, radius)| (player_pos - pos).norm() < radius)
+        });
+
+        // Each enemy goes after the nearest unit its faction is hostile to
+        let targets: Vec<Positional> = data
+            .enemies
+            .iter()
+            .map(|enemy| {
+                closest_hostile(data, enemy.faction(), enemy.positional().position)
+                    .unwrap_or(data.player.positional)
+            })
+            .collect();
+
+        for (i, enemy) in &mut data.enemies.iter_mut().enumerate() {
+            enemy.set_player_spotted(player_spotted);
             enemy.update(
                 settings,
-                data.player.positional,
+                targets[i],
                 None,
                 &mut data.world,
                 &mut data.bullets,
@@ -145,22 +291,55 @@ impl RoundPhase {
             );
         }
 
+        for ally in data.allies.iter_mut() {
+            ally.update(
+                data.player.positional.position,
+                &data.enemies,
+                &data.factions,
+                &mut data.world,
+                &mut data.bullets,
+            );
+        }
+
         {
             let look_ahead = 0.0;
             let stiffness = 0.3;
             self.update_camera(data, data.player.positional, look_ahead, stiffness);
         }
 
+        self.handle_enemy_requests(data, ctx);
         self.maintain_weapons(data);
+        self.maintain_grenades(data);
+        self.maintain_hazards(data);
         data.maintain_walls();
         self.maintain_enemies(data);
+        self.maintain_wrecks(data);
+        self.maintain_pickups(data, ctx);
+        self.maintain_allies(data);
+        self.maintain_house(data);
+        self.maintain_waves(data, ctx);
+
+        // Checked every frame, since the last wave may spawn nobody at all
+        if settings.enemies && data.enemies.is_empty() && self.pending_waves.is_empty() {
+            self.victory = true;
+        }
 
         if !data.player.alive() {
             self.failure = true;
         }
 
-        if data.wall_pieces.is_empty() {
-            self.failure = true;
+        // Without a house on the map, losing every wall loses the ranch
+        match data.house {
+            Some(ref house) => {
+                if !house.alive() {
+                    self.failure = true;
+                }
+            }
+            None => {
+                if data.wall_pieces.is_empty() {
+                    self.failure = true;
+                }
+            }
         }
 
         data.world.step();
@@ -172,8 +351,7 @@ impl RoundPhase {
             let mut hit_victim = BulletHitVictim::None;
 
             let mut hit_anything = false;
-            let mut groups = CollisionGroups::new();
-            groups.set_blacklist(&[bullet.allegiance]);
+            let groups = data.factions.bullet_groups(bullet.faction);
             for (other_collider, collision) in collision_world.interferences_with_ray(
                 &Ray {
                     origin: bullet.pos.position,
@@ -186,9 +364,15 @@ impl RoundPhase {
                     if other_body == Some(data.player.body_handle) {
                         hit_victim = BulletHitVictim::Player;
                     } else {
+                        let hit_pos = bullet.pos.position + bullet.pos.forward() * collision.toi;
                         for (enemy_i, enemy) in data.enemies.iter().enumerate() {
                             if enemy.rigid_body() == other_body {
-                                hit_victim = BulletHitVictim::Enemy(enemy_i);
+                                hit_victim = BulletHitVictim::Enemy(enemy_i, hit_pos);
+                            }
+                        }
+                        for (ally_i, ally) in data.allies.iter().enumerate() {
+                            if Some(ally.pawn.body_handle) == other_body {
+                                hit_victim = BulletHitVictim::Ally(ally_i);
                             }
                         }
                     }
@@ -201,12 +385,25 @@ impl RoundPhase {
             if hit_anything {
                 bullet.life_seconds = 0.0;
                 match hit_victim {
-                    BulletHitVictim::Enemy(enemy_i) => {
-                        data.enemies[enemy_i].damage(bullet.damage);
+                    BulletHitVictim::Enemy(enemy_i, hit_pos) => {
+                        data.enemies[enemy_i].damage_at(bullet.damage, hit_pos);
+                        for effect in &bullet.effects {
+                            data.enemies[enemy_i].apply_status(effect);
+                        }
+                        data.sounds.play_bullet_hit();
+                    }
+                    BulletHitVictim::Ally(ally_i) => {
+                        data.allies[ally_i].damage(bullet.damage);
+                        for effect in &bullet.effects {
+                            data.allies[ally_i].pawn.status.apply(effect);
+                        }
                         data.sounds.play_bullet_hit();
                     }
                     BulletHitVictim::Player => {
                         data.player.damage(bullet.damage);
+                        for effect in &bullet.effects {
+                            data.player.status.apply(effect);
+                        }
                         data.sounds.play_bullet_hit();
                     }
                     BulletHitVictim::None => {
@@ -224,27 +421,440 @@ impl RoundPhase {
         data.bullets.retain(|b| b.life_seconds > 0.0);
     }
 
+    fn taunt(&mut self, data: &mut WorldData) {
+        data.sounds.play_taunt();
+
+        // Rubbing it in only works so often
+        if self.elapsed_seconds - self.last_taunt_seconds < MORALE_TAUNT_COOLDOWN_SECONDS {
+            return;
+        }
+        self.last_taunt_seconds = self.elapsed_seconds;
+
+        let player_pos = data.player.positional.position;
+        for enemy in data.enemies.iter_mut() {
+            if (enemy.positional().position - player_pos).norm() < MORALE_TAUNT_RADIUS {
+                enemy.shake_morale(MORALE_TAUNT_SHOCK);
+            }
+        }
+    }
+
     fn maintain_enemies(&mut self, data: &mut WorldData) {
+        // Routed enemies that make it to the walls around the map are out of the fight
+        let (min, max) = map_bounds(&data.map);
+
+        let mut enemies_escaped = Vec::new();
+        for (i, e) in data.enemies.iter().enumerate() {
+            let pos = e.positional().position;
+            let at_edge = pos.x < min.x + ESCAPE_MARGIN
+                || pos.x > max.x - ESCAPE_MARGIN
+                || pos.y < min.y + ESCAPE_MARGIN
+                || pos.y > max.y - ESCAPE_MARGIN;
+            if e.routed() && at_edge {
+                data.world.remove_bodies(&[e.rigid_body().unwrap()]);
+                enemies_escaped.push(i);
+            }
+        }
+
+        for i in enemies_escaped.iter().rev() {
+            let enemy = data.enemies.swap_remove(*i);
+            data.forget_body(enemy.rigid_body().unwrap());
+        }
+        self.routed_count += enemies_escaped.len() as u32;
+
         let mut enemies_killed = Vec::new();
+        let mut death_positions = Vec::new();
         for (i, e) in data.enemies.iter().enumerate() {
             if e.health() <= 0.0 {
-                data.world.remove_bodies(&[e.rigid_body().unwrap()]);
+                let pos = e.positional().position;
+                let wreck = e.wreck();
+
+                // Spread the drops around the body, clear of any wreck left where it fell
+                let loot = e.drop_loot();
+                let drop_radius = wreck
+                    .as_ref()
+                    .map_or(LOOT_SPREAD, |wreck| wreck.clearance() + PICKUP_RADIUS);
+                for (j, kind) in loot.iter().enumerate() {
+                    let a = j as f32 / loot.len() as f32 * std::f32::consts::PI * 2.0;
+                    let offset = Vector2::new(a.cos(), a.sin()) * drop_radius;
+                    data.pickups
+                        .push(Pickup::spawn(&mut data.world, *kind, pos + offset));
+                }
+
+                death_positions.push(pos);
+                if let Some(wreck) = wreck {
+                    wreck.settle(&mut data.world);
+                    data.wrecks.push(wreck);
+                } else {
+                    data.world.remove_bodies(&[e.rigid_body().unwrap()]);
+                }
                 enemies_killed.push(i);
             }
         }
 
         for i in enemies_killed.iter().rev() {
-            data.enemies.swap_remove(*i);
+            let enemy = data.enemies.swap_remove(*i);
+            data.forget_body(enemy.rigid_body().unwrap());
+        }
+
+        for pos in death_positions {
+            for enemy in data.enemies.iter_mut() {
+                if (enemy.positional().position - pos).norm() < MORALE_DEATH_RADIUS {
+                    enemy.shake_morale(MORALE_DEATH_SHOCK);
+                }
+            }
         }
 
         if !enemies_killed.is_empty() {
-            data.sounds.play_taunt();
-            if data.enemies.is_empty() {
-                self.victory = true;
+            self.taunt(data);
+        }
+    }
+
+    fn maintain_wrecks(&mut self, data: &mut WorldData) {
+        for wreck in data.wrecks.iter_mut() {
+            wreck.life_seconds -= 1.0 / DESIRED_FPS as f32;
+        }
+
+        let expired: Vec<BodyHandle> = data
+            .wrecks
+            .iter()
+            .filter(|w| w.life_seconds <= 0.0)
+            .map(|w| w.rigid_body)
+            .collect();
+        for body in expired {
+            data.world.remove_bodies(&[body]);
+            data.forget_body(body);
+        }
+
+        data.wrecks.retain(|w| w.life_seconds > 0.0);
+    }
+
+    fn maintain_pickups(&mut self, data: &mut WorldData, ctx: &mut Context) {
+        for event in data.world.proximity_events().iter() {
+            let touching = event.new_status == Proximity::Intersecting;
+            for &(sensor, other) in [
+                (event.collider1, event.collider2),
+                (event.collider2, event.collider1),
+            ]
+            .iter()
+            {
+                if data.world.collider_body_handle(other) != Some(data.player.body_handle) {
+                    continue;
+                }
+                if let Some(pickup) = data.pickups.iter_mut().find(|p| p.collider == sensor) {
+                    pickup.touching = touching;
+                }
+            }
+        }
+
+        let touched: Vec<(usize, LootKind)> = data
+            .pickups
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.touching)
+            .map(|(i, p)| (i, p.kind))
+            .collect();
+
+        for (i, kind) in touched {
+            if self.collect(kind, data, ctx) {
+                data.pickups[i].life_seconds = 0.0;
+            }
+        }
+
+        for pickup in data.pickups.iter_mut() {
+            pickup.life_seconds -= 1.0 / DESIRED_FPS as f32;
+        }
+
+        for pickup in data.pickups.iter().filter(|p| p.life_seconds <= 0.0) {
+            data.world.remove_colliders(&[pickup.collider]);
+        }
+
+        data.pickups.retain(|p| p.life_seconds > 0.0);
+
+        if let Some((_, ref mut seconds)) = self.notice {
+            *seconds -= 1.0 / DESIRED_FPS as f32;
+        }
+        if self
+            .notice
+            .as_ref()
+            .map_or(false, |(_, seconds)| *seconds <= 0.0)
+        {
+            self.notice = None;
+        }
+    }
+
+    // Hands a pickup to the player; anything they can't use yet stays on the ground
+    fn collect(&mut self, kind: LootKind, data: &mut WorldData, ctx: &mut Context) -> bool {
+        let taken = match kind {
+            LootKind::Ammo => data.player.weapon.add_ammo(AMMO_PICKUP_ROUNDS),
+            LootKind::Medkit => {
+                if data.player.health < data.player.max_health() {
+                    data.player.heal(MEDKIT_HEALTH);
+                    true
+                } else {
+                    false
+                }
+            }
+            LootKind::Smg => {
+                if !data.player.weapon.add_ammo(AMMO_PICKUP_ROUNDS) {
+                    data.player.equip(Weapon::from_config(
+                        ctx,
+                        WeaponConfig::from_toml(LOOTED_SMG),
+                    ));
+                }
+                true
+            }
+            LootKind::Scrap => {
+                data.scrap += 1;
+                true
+            }
+        };
+
+        if taken {
+            data.sounds.play_pickup();
+            self.notice = Some((format!("Picked up {}", kind.name()), NOTICE_SECONDS));
+        }
+
+        taken
+    }
+
+    fn maintain_allies(&mut self, data: &mut WorldData) {
+        let mut allies_killed = Vec::new();
+        for (i, ally) in data.allies.iter().enumerate() {
+            if !ally.alive() {
+                data.world.remove_bodies(&[ally.pawn.body_handle]);
+                allies_killed.push(i);
+            }
+        }
+
+        for i in allies_killed.iter().rev() {
+            let ally = data.allies.swap_remove(*i);
+            data.forget_body(ally.pawn.body_handle);
+        }
+
+        if !allies_killed.is_empty() {
+            data.sounds.play_death();
+        }
+    }
+
+    fn update_breaches(&mut self, data: &WorldData) {
+        if data.wall_pieces.len() == self.breach_wall_count {
+            return;
+        }
+        self.breach_wall_count = data.wall_pieces.len();
+
+        self.breaches = self
+            .house_walls
+            .iter()
+            .filter(|(rb, _)| !data.wall_pieces.iter().any(|wp| wp.rb == *rb))
+            .map(|(_, pos)| *pos)
+            .collect();
+        if self.breaches.is_empty() {
+            return;
+        }
+
+        let mut blocked = self.solid_cells.clone();
+        for wp in data.wall_pieces.iter() {
+            if let Some((x, y)) = world_to_map_cell(&data.map, wp.anchor) {
+                blocked[y][x] = true;
+            }
+        }
+
+        let starts: Vec<_> = self
+            .breaches
+            .iter()
+            .filter_map(|&pos| world_to_map_cell(&data.map, pos))
+            .collect();
+        self.breach_cells = reachable_cells(&blocked, &starts);
+    }
+
+    fn maintain_house(&mut self, data: &mut WorldData) {
+        let dt = 1.0 / DESIRED_FPS as f32;
+        self.update_breaches(data);
+
+        let map = &data.map;
+        let breach_cells = &self.breach_cells;
+        let can_reach_breach =
+            |pos: Point2| world_to_map_cell(map, pos).map_or(false, |(x, y)| breach_cells[y][x]);
+
+        if let Some(ref mut house) = data.house {
+            house.update(dt);
+
+            for enemy in data.enemies.iter_mut() {
+                let pos = enemy.positional().position;
+
+                // Enemies that can get to a gap in the house walls go through it and on to
+                // the house; the rest keep at it from outside
+                let nearest_breach = self
+                    .breaches
+                    .iter()
+                    .min_by(|a, b| (**a - pos).norm().partial_cmp(&(**b - pos).norm()).unwrap());
+                let objective = match nearest_breach {
+                    Some(&breach)
+                        if house.contains(pos) || (breach - pos).norm() < BREACH_ENTER_DISTANCE =>
+                    {
+                        Some(house.center)
+                    }
+                    Some(&breach) if can_reach_breach(pos) => Some(breach),
+                    _ => None,
+                };
+
+                enemy.set_objective(objective);
+                if house.contains(pos) {
+                    house.damage(enemy.siege_damage() * dt);
+                }
+            }
+        }
+    }
+
+    fn maintain_waves(&mut self, data: &mut WorldData, ctx: &mut Context) {
+        let elapsed = self.elapsed_seconds;
+        let enemies_remaining = data.enemies.len() as u32;
+        let wall_breached = data.wall_pieces.len() < self.initial_wall_count;
+
+        let mut ready_waves = Vec::new();
+        for (i, wave) in self.pending_waves.iter_mut().enumerate() {
+            if !wave.announced {
+                if let Some(t) = wave.cfg.trigger.seconds_until(elapsed) {
+                    if t < WAVE_ANNOUNCE_SECONDS {
+                        data.sounds.play_reinforcements();
+                        wave.announced = true;
+                    }
+                }
             }
+
+            if wave
+                .cfg
+                .trigger
+                .is_ready(elapsed, enemies_remaining, wall_breached)
+            {
+                ready_waves.push(i);
+            }
+        }
+
+        // Nobody left to breach walls or die off; don't leave the player waiting forever
+        if ready_waves.is_empty() && enemies_remaining == 0 && !self.pending_waves.is_empty() {
+            ready_waves.push(0);
+        }
+
+        for i in ready_waves.into_iter().rev() {
+            let wave = self.pending_waves.remove(i);
+            if !wave.announced {
+                data.sounds.play_reinforcements();
+            }
+
+            self.spawn_bulldozers(data, ctx, wave.cfg.dozers as usize);
+            self.spawn_swat(data, ctx, wave.cfg.swat as usize);
+            self.spawn_bosses(data, ctx, wave.cfg.bosses as usize);
+            self.spawn_archetype(data, ctx, "k9", wave.cfg.k9 as usize);
+            self.spawn_archetype(data, ctx, "sniper", wave.cfg.snipers as usize);
+            self.spawn_archetype(data, ctx, "helicopter", wave.cfg.helicopters as usize);
         }
     }
 
+    fn handle_enemy_requests(&mut self, data: &mut WorldData, ctx: &mut Context) {
+        let requests: Vec<_> = data
+            .enemies
+            .iter_mut()
+            .flat_map(|enemy| enemy.drain_requests())
+            .collect();
+
+        for request in requests {
+            match request {
+                EnemyRequest::Reinforce {
+                    archetype,
+                    count,
+                    position,
+                } => {
+                    data.sounds.play_swat_gogogo();
+                    for i in 0..count {
+                        let offset = Vector2::new(i as f32 * -1.5, 0.0);
+                        let enemy = spawn_enemy(ctx, data, &archetype, position + offset, 0.0);
+                        data.enemies.push(enemy);
+                    }
+                }
+                EnemyRequest::TearGas { position } => {
+                    data.hazards.push(GasCloud::new(&data.tear_gas, position));
+                }
+                EnemyRequest::Grenade { kind, from, target } => {
+                    let grenade = Grenade::throw(&mut data.world, kind, from, target);
+                    data.grenades.push(grenade);
+                }
+            }
+        }
+    }
+
+    fn maintain_grenades(&mut self, data: &mut WorldData) {
+        let dt = 1.0 / DESIRED_FPS as f32;
+        for grenade in data.grenades.iter_mut() {
+            grenade.update(&mut data.world, dt);
+        }
+
+        let mut detonated = Vec::new();
+        for (i, grenade) in data.grenades.iter().enumerate() {
+            if grenade.fuse_done() {
+                match grenade.detonate(&data.world, data.player.positional.position, &data.tear_gas)
+                {
+                    Detonation::Gas(cloud) => data.hazards.push(cloud),
+                    Detonation::Flash(seconds) => {
+                        data.flash_seconds = data.flash_seconds.max(seconds)
+                    }
+                }
+                data.world.remove_bodies(&[grenade.rigid_body]);
+                detonated.push(i);
+            }
+        }
+
+        for i in detonated.iter().rev() {
+            let grenade = data.grenades.swap_remove(*i);
+            data.forget_body(grenade.rigid_body);
+        }
+
+        if !detonated.is_empty() {
+            data.sounds.play_grenade();
+        }
+
+        data.flash_seconds = (data.flash_seconds - dt).max(0.0);
+    }
+
+    fn maintain_hazards(&mut self, data: &mut WorldData) {
+        let dt = 1.0 / DESIRED_FPS as f32;
+        let mut speed_scale = 1.0f32;
+        for cloud in data.hazards.iter_mut() {
+            if data.player.alive() && cloud.contains(data.player.positional.position) {
+                data.player.damage(cloud.damage_per_second * dt);
+                speed_scale = speed_scale.min(cloud.slow);
+                for effect in &cloud.effects {
+                    data.player.status.apply(effect);
+                }
+            }
+            for ally in data.allies.iter_mut() {
+                if cloud.contains(ally.pawn.positional.position) {
+                    for effect in &cloud.effects {
+                        ally.pawn.status.apply(effect);
+                    }
+                }
+            }
+            cloud.life_seconds -= dt;
+        }
+
+        data.hazards.retain(|cloud| cloud.life_seconds > 0.0);
+        data.player.set_speed_scale(speed_scale);
+
+        // Eyes water up quickly in the gas and take a while to clear
+        if speed_scale < 1.0 {
+            data.gas_exposure = (data.gas_exposure + dt * 2.0).min(1.0);
+        } else {
+            data.gas_exposure = (data.gas_exposure - dt * 0.3).max(0.0);
+        }
+    }
+
+    fn next_timed_wave_seconds(&self) -> Option<f32> {
+        self.pending_waves
+            .iter()
+            .filter_map(|wave| wave.cfg.trigger.seconds_until(self.elapsed_seconds))
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+
     pub fn draw(&mut self, _settings: &Settings, data: &mut WorldData, ctx: &mut Context) {
         let window_size = graphics::drawable_size(ctx);
         let identity_transform = graphics::transform(ctx);
@@ -254,32 +864,36 @@ impl RoundPhase {
 
         graphics::clear(ctx, [0.1, 0.2, 0.3, 1.0].into());
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Background",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
-        }
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Background",
+            data.screen_to_world,
+        );
 
-        {
-            MainState::draw_wall_pieces(&data.wall_pieces, &data.world, &mut data.map_spritebatch);
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
+        MainState::draw_wall_pieces(
+            &data.wall_pieces,
+            &data.world,
+            &mut data.map_graphics,
+            &data.map,
+        );
+        data.map_graphics.draw_and_clear(ctx);
+
+        draw_map_layer(
+            ctx,
+            &data.map_graphics,
+            &data.map,
+            "Props",
+            data.screen_to_world,
+        );
+
+        for wreck in &data.wrecks {
+            wreck.draw(ctx);
         }
 
-        {
-            draw_map_layer(
-                &mut data.map_spritebatch,
-                &data.map,
-                &data.map_tile_image,
-                "Props",
-            );
-            graphics::draw(ctx, &data.map_spritebatch, graphics::DrawParam::new()).unwrap();
-            data.map_spritebatch.clear();
+        for pickup in &data.pickups {
+            pickup.draw(ctx);
         }
 
         self.draw_bullets(data, ctx);
@@ -292,24 +906,57 @@ impl RoundPhase {
             enemy.draw(ctx);
         }
 
+        for ally in &data.allies {
+            ally.draw();
+        }
+
         {
             let character_spritebatch = &mut *data.character_spritebatch.borrow_mut();
             graphics::draw(ctx, character_spritebatch, graphics::DrawParam::new()).unwrap();
             character_spritebatch.clear();
         }
 
+        for grenade in &data.grenades {
+            grenade.draw(ctx);
+        }
+
+        for cloud in &data.hazards {
+            cloud.draw(ctx);
+        }
+
+        if self.debug_overlay {
+            draw_ai_debug(data, ctx);
+        }
+
         // Reset to identity transform for text and splash screen
         graphics::set_transform(ctx, identity_transform);
         graphics::apply_transformations(ctx).unwrap();
 
+        self.draw_vision_effects(data, ctx);
+
+        if self.debug_overlay {
+            draw_ai_debug_labels(data, ctx);
+        }
+
         let health_text = graphics::Text::new((
             format!("Health: {:.0}", data.player.health() * 100.0),
             data.font,
             64.0,
         ));
 
-        let enemies_text =
-            graphics::Text::new((format!("Enemies: {}", data.enemies.len()), data.font, 64.0));
+        let enemies_text = if self.routed_count > 0 {
+            graphics::Text::new((
+                format!(
+                    "Enemies: {}  Routed: {}",
+                    data.enemies.len(),
+                    self.routed_count
+                ),
+                data.font,
+                64.0,
+            ))
+        } else {
+            graphics::Text::new((format!("Enemies: {}", data.enemies.len()), data.font, 64.0))
+        };
 
         let mut height = 0.0;
         draw_shadowed_text(
@@ -326,6 +973,56 @@ impl RoundPhase {
             Color::from((255, 255, 255, 255)),
         );
 
+        height += 20.0 + enemies_text.height(ctx) as f32;
+        let radio_text = match data.player.weapon.ammo() {
+            Some(ammo) => graphics::Text::new((
+                format!("Radio: {}  Ammo: {}", self.radio_charges, ammo),
+                data.font,
+                64.0,
+            )),
+            None => {
+                graphics::Text::new((format!("Radio: {}", self.radio_charges), data.font, 64.0))
+            }
+        };
+        draw_shadowed_text(
+            ctx,
+            Point2::new(50.0, 20.0 + height),
+            &radio_text,
+            Color::from((255, 255, 255, 255)),
+        );
+
+        if let Some(seconds) = self.next_timed_wave_seconds() {
+            height += 20.0 + radio_text.height(ctx) as f32;
+            let reinforcements_text = graphics::Text::new((
+                format!("Reinforcements: {:.0}", seconds.ceil()),
+                data.font,
+                64.0,
+            ));
+            draw_shadowed_text(
+                ctx,
+                Point2::new(50.0, 20.0 + height),
+                &reinforcements_text,
+                Color::from((255, 0, 0, 255)),
+            );
+        }
+
+        self.draw_house_bar(data, ctx);
+        self.draw_boss_bar(data, ctx);
+
+        if let Some((ref notice, _)) = self.notice {
+            let text = graphics::Text::new((notice.as_str(), data.font, 64.0));
+            let text_width = text.width(ctx) as f32;
+            draw_shadowed_text(
+                ctx,
+                Point2::new(
+                    (window_size.0 as f32 - text_width) / 2.0,
+                    window_size.1 as f32 * 0.7,
+                ),
+                &text,
+                Color::from((255, 220, 120, 255)),
+            );
+        }
+
         let text =
             graphics::Text::new((format!("Round {}", self.round_index + 1), data.font, 96.0));
         let text_width = text.width(ctx) as f32;
@@ -342,6 +1039,143 @@ impl RoundPhase {
         );
     }
 
+    fn draw_vision_effects(&mut self, data: &WorldData, ctx: &mut Context) {
+        let window_size = graphics::drawable_size(ctx);
+        let screen = Rect::new(0.0, 0.0, window_size.0 as f32, window_size.1 as f32);
+
+        let overlays = [
+            (data.gas_exposure * 0.4, Color::new(0.75, 0.8, 0.55, 1.0)),
+            (data.flash_seconds.min(1.0), Color::new(1.0, 1.0, 1.0, 1.0)),
+            (
+                if data.player.status.blinded() {
+                    0.85
+                } else {
+                    0.0
+                },
+                Color::new(0.0, 0.0, 0.0, 1.0),
+            ),
+        ];
+
+        for (alpha, color) in overlays.iter() {
+            if *alpha > 0.0 {
+                let mesh = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    screen,
+                    Color::new(color.r, color.g, color.b, *alpha),
+                )
+                .unwrap();
+                graphics::draw(ctx, &mesh, graphics::DrawParam::new()).unwrap();
+            }
+        }
+    }
+
+    fn draw_house_bar(&mut self, data: &WorldData, ctx: &mut Context) {
+        let house = match data.house {
+            Some(ref house) => house,
+            None => return,
+        };
+
+        let window_size = graphics::drawable_size(ctx);
+        let bar_width = 300.0;
+        let bar_height = 20.0;
+        let bar_x = window_size.0 as f32 - bar_width - 50.0;
+        let top = 20.0;
+
+        let text = graphics::Text::new(("Ranch", data.font, 64.0));
+        let text_width = text.width(ctx) as f32;
+        draw_shadowed_text(
+            ctx,
+            Point2::new(bar_x - text_width - 20.0, top),
+            &text,
+            Color::from((255, 255, 255, 255)),
+        );
+
+        let bar_y = top + (text.height(ctx) as f32 - bar_height) * 0.5;
+        let background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(bar_x, bar_y, bar_width, bar_height),
+            Color::from((0, 0, 0, 255)),
+        )
+        .unwrap();
+        graphics::draw(ctx, &background, graphics::DrawParam::new()).unwrap();
+
+        let fraction = house.health_fraction();
+        if fraction > 0.0 {
+            // Flashes while enemies are tearing it apart
+            let color = if house.under_attack() && (self.elapsed_seconds * 8.0).sin() > 0.0 {
+                Color::from((255, 255, 255, 255))
+            } else {
+                Color::from((200, 140, 40, 255))
+            };
+
+            let health = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(
+                    bar_x + 2.0,
+                    bar_y + 2.0,
+                    (bar_width - 4.0) * fraction.min(1.0),
+                    bar_height - 4.0,
+                ),
+                color,
+            )
+            .unwrap();
+            graphics::draw(ctx, &health, graphics::DrawParam::new()).unwrap();
+        }
+    }
+
+    fn draw_boss_bar(&mut self, data: &WorldData, ctx: &mut Context) {
+        let window_size = graphics::drawable_size(ctx);
+
+        let boss = data
+            .enemies
+            .iter()
+            .filter_map(|enemy| enemy.boss_bar())
+            .next();
+        if let Some((title, fraction)) = boss {
+            let bar_width = window_size.0 as f32 * 0.5;
+            let bar_height = 24.0;
+            let bar_x = (window_size.0 as f32 - bar_width) / 2.0;
+            let bar_y = 110.0;
+
+            let text = graphics::Text::new((title, data.font, 64.0));
+            let text_width = text.width(ctx) as f32;
+            draw_shadowed_text(
+                ctx,
+                Point2::new((window_size.0 as f32 - text_width) / 2.0, 20.0),
+                &text,
+                Color::from((255, 255, 255, 255)),
+            );
+
+            let background = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(bar_x, bar_y, bar_width, bar_height),
+                Color::from((0, 0, 0, 255)),
+            )
+            .unwrap();
+            graphics::draw(ctx, &background, graphics::DrawParam::new()).unwrap();
+
+            if fraction > 0.0 {
+                let health = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(
+                        bar_x + 2.0,
+                        bar_y + 2.0,
+                        (bar_width - 4.0) * fraction.min(1.0),
+                        bar_height - 4.0,
+                    ),
+                    Color::from((200, 0, 0, 255)),
+                )
+                .unwrap();
+                graphics::draw(ctx, &health, graphics::DrawParam::new()).unwrap();
+            }
+        }
+    }
+
     pub fn draw_bullets(&mut self, data: &mut WorldData, ctx: &mut Context) {
         for bullet in data.bullets.iter() {
             data.bullet_batch
@@ -394,7 +1228,7 @@ impl RoundPhase {
         &mut self,
         _settings: &Settings,
         data: &mut WorldData,
-        _ctx: &mut Context,
+        ctx: &mut Context,
         keycode: KeyCode,
         value: bool,
     ) {
@@ -409,7 +1243,7 @@ impl RoundPhase {
             KeyCode::Key8 => data.sounds.play_break2(),
             KeyCode::Key9 => {
                 if value {
-                    data.sounds.play_taunt()
+                    self.taunt(data)
                 }
             }
             KeyCode::Key0 => {
@@ -422,6 +1256,16 @@ impl RoundPhase {
             KeyCode::S | KeyCode::Down => data.player_input.down = value,
             KeyCode::D | KeyCode::Right => data.player_input.right = value,
             KeyCode::Tab => data.strategic_view = value,
+            KeyCode::F3 => {
+                if value {
+                    self.debug_overlay = !self.debug_overlay;
+                }
+            }
+            KeyCode::Q => {
+                if value {
+                    self.call_radio(data, ctx);
+                }
+            }
             KeyCode::Back => {
                 if value {
                     self.victory = true;
@@ -466,47 +1310,22 @@ impl RoundPhase {
     }
 }
 
-fn spawn_dozer(
-    ctx: &mut Context,
-    world: &mut World<f32>,
-    engine_sound: audio::SoundData,
-    image: Rc<graphics::Image>,
-    pos: Point2,
-    rotation: f32,
-) -> Box<dyn Enemy> {
-    let size = {
-        let rad = 3.0 / 2.0;
-        let size = Vector2::new(image.width() as f32, image.height() as f32);
-        rad * size / size.x.min(size.y)
-    };
-
-    let geom = ShapeHandle::new(Cuboid::new(size));
-    let inertia = geom.inertia(1.0);
-    let center_of_mass = geom.center_of_mass();
-
-    let pos = Isometry2::new(Vector2::new(pos.x, pos.y), rotation);
-    let rb = world.add_rigid_body(pos, inertia, center_of_mass);
-
-    let collider_handle = world.add_collider(
-        COLLIDER_MARGIN,
-        geom.clone(),
-        rb,
-        Isometry2::identity(),
-        Material::new(0.3, 0.5),
-    );
-
-    let mut col_group = CollisionGroups::new();
-    col_group.set_membership(&[GROUP_ENEMY]);
-    world
-        .collision_world_mut()
-        .set_collision_groups(collider_handle, col_group);
-
-    Box::new(Bulldozer::new(
-        ctx,
-        engine_sound,
-        rb,
-        image.clone(),
-        Positional::default(),
-        Some(Box::new(EnemyDozerBehavior::new())),
-    ))
+fn closest_hostile(data: &WorldData, faction: Faction, from: Point2) -> Option<Positional> {
+    let player = Some((Faction::Player, data.player.positional)).filter(|_| data.player.alive());
+    let allies = data
+        .allies
+        .iter()
+        .filter(|ally| ally.alive())
+        .map(|ally| (ally.pawn.faction(), ally.pawn.positional));
+
+    player
+        .into_iter()
+        .chain(allies)
+        .filter(|(target, _)| data.factions.is_hostile(faction, *target))
+        .map(|(_, positional)| positional)
+        .min_by(|a, b| {
+            let da = (a.position - from).norm();
+            let db = (b.position - from).norm();
+            da.partial_cmp(&db).unwrap()
+        })
 }
@@ -10,6 +10,48 @@ pub fn load_settings() -> Settings {
     toml::from_str(&settings_toml).unwrap()
 }
 
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WaveTrigger {
+    Time { seconds: f32 },
+    EnemiesRemaining { count: u32 },
+    WallBreached,
+}
+
+impl WaveTrigger {
+    pub fn is_ready(&self, elapsed: f32, enemies_remaining: u32, wall_breached: bool) -> bool {
+        match *self {
+            WaveTrigger::Time { seconds } => elapsed >= seconds,
+            WaveTrigger::EnemiesRemaining { count } => enemies_remaining <= count,
+            WaveTrigger::WallBreached => wall_breached,
+        }
+    }
+
+    pub fn seconds_until(&self, elapsed: f32) -> Option<f32> {
+        match *self {
+            WaveTrigger::Time { seconds } => Some((seconds - elapsed).max(0.0)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WaveConfig {
+    pub trigger: WaveTrigger,
+    #[serde(default)]
+    pub dozers: u32,
+    #[serde(default)]
+    pub swat: u32,
+    #[serde(default)]
+    pub bosses: u32,
+    #[serde(default)]
+    pub k9: u32,
+    #[serde(default)]
+    pub snipers: u32,
+    #[serde(default)]
+    pub helicopters: u32,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub music: bool,
@@ -17,28 +59,49 @@ pub struct Settings {
     pub sounds: bool,
     pub enemies: bool,
 
+    // Seed for the generated ranch, so a layout can be played again; a fresh seed is
+    // picked for every campaign when absent. `--seed <number>` on the command line sets it.
+    #[serde(default)]
+    pub map_seed: Option<u64>,
+
+    // Radio calls per round, and how many ranch hands each one brings
+    #[serde(default)]
+    pub radio_charges: u32,
+    #[serde(default)]
+    pub radio_allies: u32,
+
     pub round1_dozers: u32,
     pub round1_swat: u32,
     pub round1_crates: u32,
     pub round1_rocks: u32,
+    #[serde(default)]
+    pub round1_waves: Vec<WaveConfig>,
 
     pub round2_dozers: u32,
     pub round2_swat: u32,
     pub round2_crates: u32,
     pub round2_rocks: u32,
+    #[serde(default)]
+    pub round2_waves: Vec<WaveConfig>,
 
     pub round3_dozers: u32,
     pub round3_swat: u32,
     pub round3_crates: u32,
     pub round3_rocks: u32,
+    #[serde(default)]
+    pub round3_waves: Vec<WaveConfig>,
 
     pub round4_dozers: u32,
     pub round4_swat: u32,
     pub round4_crates: u32,
     pub round4_rocks: u32,
+    #[serde(default)]
+    pub round4_waves: Vec<WaveConfig>,
 
     pub round5_dozers: u32,
     pub round5_swat: u32,
     pub round5_crates: u32,
     pub round5_rocks: u32,
+    #[serde(default)]
+    pub round5_waves: Vec<WaveConfig>,
 }
@@ -0,0 +1,250 @@
+use crate::{
+    clamp_norm, graphics, is_point_visible, map_bounds, map_px_to_world, reachable_cells,
+    solid_prop_cells, world_to_map_cell, AiDebug, BodyHandle, Bullet, Color, Context, Enemy,
+    Faction, Movement, PawnInput, Player, Point2, Positional, Settings, Sounds, StatusEffectConfig,
+    Vector2, World, WorldData, SWAT_OUTER_RADIUS,
+};
+
+use super::player::VisualState;
+
+use rand::Rng;
+
+// Perches keep this far from the walls around the map edges
+const PERCH_EDGE_MARGIN: f32 = 1.0;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct SniperConfig {
+    // Perches are picked between these distances from the ranch
+    pub min_radius: f32,
+    pub max_radius: f32,
+    pub move_speed: f32,
+    // How long the laser is on the player before the shot
+    pub aim_seconds: f32,
+}
+
+impl Default for SniperConfig {
+    fn default() -> Self {
+        Self {
+            min_radius: SWAT_OUTER_RADIUS * 1.75,
+            max_radius: SWAT_OUTER_RADIUS * 2.25,
+            move_speed: 0.6,
+            aim_seconds: 1.5,
+        }
+    }
+}
+
+pub struct Sniper {
+    pawn: Player,
+    cfg: SniperConfig,
+    perch: Point2,
+    aim_target: Option<Point2>,
+    aim_seconds: f32,
+    player_spotted: bool,
+}
+
+// Picks a perch the sniper can walk to from `spawn`, between the configured distances
+// from the ranch. Maps too small or too cluttered for that get the reachable spot
+// closest to the middle of that band; a spawn stuck in a solid cell stays put.
+pub fn pick_perch(data: &WorldData, spawn: Point2, cfg: &SniperConfig) -> Point2 {
+    let map = &data.map;
+    let start = match world_to_map_cell(map, spawn) {
+        Some(start) => start,
+        None => return spawn,
+    };
+
+    let mut blocked = solid_prop_cells(map, &data.tile_catalog);
+    for wall_piece in &data.wall_pieces {
+        if let Some((x, y)) = world_to_map_cell(map, wall_piece.anchor) {
+            blocked[y][x] = true;
+        }
+    }
+    let reached = reachable_cells(&blocked, &[start]);
+
+    let ranch = data.markers.ranch_center();
+    let (min, max) = map_bounds(map);
+    let inside = |pos: Point2| {
+        pos.x > min.x + PERCH_EDGE_MARGIN
+            && pos.x < max.x - PERCH_EDGE_MARGIN
+            && pos.y > min.y + PERCH_EDGE_MARGIN
+            && pos.y < max.y - PERCH_EDGE_MARGIN
+    };
+
+    let mut spots = Vec::new();
+    for (y, row) in reached.iter().enumerate() {
+        for (x, &open) in row.iter().enumerate() {
+            let pos = map_px_to_world(
+                map,
+                (x as f32 + 0.5) * map.tile_width as f32,
+                (y as f32 + 0.5) * map.tile_height as f32,
+            );
+            if open && inside(pos) {
+                spots.push(pos);
+            }
+        }
+    }
+
+    let in_band: Vec<Point2> = spots
+        .iter()
+        .cloned()
+        .filter(|pos| {
+            let distance = (*pos - ranch).norm();
+            distance >= cfg.min_radius && distance <= cfg.max_radius
+        })
+        .collect();
+
+    if !in_band.is_empty() {
+        return in_band[rand::thread_rng().gen_range(0, in_band.len())];
+    }
+
+    let band_middle = (cfg.min_radius + cfg.max_radius) * 0.5;
+    let off_band = |pos: &Point2| ((*pos - ranch).norm() - band_middle).abs();
+    spots
+        .iter()
+        .min_by(|a, b| off_band(a).partial_cmp(&off_band(b)).unwrap())
+        .cloned()
+        .unwrap_or(spawn)
+}
+
+impl Sniper {
+    pub fn new(pawn: Player, cfg: SniperConfig, perch: Point2) -> Self {
+        Sniper {
+            pawn,
+            cfg,
+            perch,
+            aim_target: None,
+            aim_seconds: 0.0,
+            player_spotted: false,
+        }
+    }
+
+    fn aim_time(&self) -> f32 {
+        // A spotlight on the player makes for an easy mark
+        if self.player_spotted {
+            self.cfg.aim_seconds * 0.5
+        } else {
+            self.cfg.aim_seconds
+        }
+    }
+}
+
+impl Enemy for Sniper {
+    fn update(
+        &mut self,
+        _settings: &Settings,
+        player_pos: Positional,
+        _movement: Option<Movement>,
+        world: &mut World<f32>,
+        bullets_out: &mut Vec<Bullet>,
+        _sounds: &mut Sounds,
+    ) {
+        let pos = self.pawn.positional.position;
+        let to_perch = self.perch - pos;
+        let perched = to_perch.norm() < 1.0;
+
+        let player_visible = perched
+            && !self.pawn.status.blinded()
+            && is_point_visible(world, pos, player_pos.position, self.pawn.faction().group());
+
+        let mut shoot = false;
+        if player_visible {
+            self.aim_target = Some(player_pos.position);
+            self.aim_seconds += 1.0 / 60.0;
+            if self.aim_seconds >= self.aim_time() {
+                shoot = true;
+                self.aim_seconds = 0.0;
+            }
+        } else {
+            self.aim_target = None;
+            self.aim_seconds = 0.0;
+        }
+
+        let movement = if perched {
+            Vector2::zeros()
+        } else {
+            clamp_norm(to_perch, self.cfg.move_speed)
+        };
+
+        self.pawn.set_input(PawnInput {
+            movement: Movement {
+                right: movement.x,
+                forward: movement.y,
+            },
+            shoot,
+            aim_pos: self.aim_target.unwrap_or(Point2::origin()),
+        });
+
+        self.pawn.set_visual(VisualState::Gun);
+        self.pawn.update(world, bullets_out);
+    }
+
+    fn rigid_body(&self) -> Option<BodyHandle> {
+        Some(self.pawn.body_handle)
+    }
+
+    fn draw(&self, ctx: &mut Context) {
+        self.pawn.draw();
+
+        // The laser brightens as the shot lines up
+        if let Some(target) = self.aim_target {
+            let t = (self.aim_seconds / self.aim_time()).min(1.0);
+            let laser = graphics::Mesh::new_line(
+                ctx,
+                &[self.pawn.positional.position, target],
+                0.05,
+                Color::new(1.0, 0.0, 0.0, 0.3 + 0.6 * t),
+            )
+            .unwrap();
+            graphics::draw(ctx, &laser, graphics::DrawParam::new()).unwrap();
+        }
+    }
+
+    fn health(&self) -> f32 {
+        self.pawn.health
+    }
+
+    fn damage(&mut self, amount: f32) {
+        self.pawn.damage(amount);
+    }
+
+    fn alive(&self) -> bool {
+        self.pawn.alive()
+    }
+
+    fn closest_target(&self) -> Option<Point2> {
+        self.aim_target
+    }
+
+    fn apply_status(&mut self, effect: &StatusEffectConfig) {
+        self.pawn.status.apply(effect);
+    }
+
+    fn faction(&self) -> Faction {
+        self.pawn.faction()
+    }
+    fn positional(&self) -> Positional {
+        self.pawn.positional
+    }
+
+    fn set_player_spotted(&mut self, spotted: bool) {
+        self.player_spotted = spotted;
+    }
+
+    fn ai_debug(&self) -> AiDebug {
+        let pos = self.pawn.positional.position;
+        let state = if (self.perch - pos).norm() >= 1.0 {
+            "moving to perch".to_string()
+        } else if self.aim_target.is_some() {
+            format!("aiming {:.1}s", self.aim_seconds)
+        } else {
+            "watching".to_string()
+        };
+
+        AiDebug {
+            state,
+            target: self.aim_target,
+            path: vec![pos, self.perch],
+            sight: None,
+        }
+    }
+}
@@ -1,18 +1,25 @@
 use ggez::audio;
 use ggez::Context;
 use rand::{thread_rng, Rng};
+use std::collections::HashMap;
 
 pub struct Sounds {
     death: audio::Source,
     break1: audio::Source,
     break2: audio::Source,
     swat_gogogo: audio::Source,
+    reinforcements: audio::Source,
+    radio: audio::Source,
+    grenade: audio::Source,
+    pickup: audio::Source,
     taunts: Vec<audio::Source>,
     swat: Vec<audio::Source>,
     crash: Vec<audio::Source>,
     ricochet: Vec<audio::Source>,
     bullet_hit: Vec<audio::Source>,
     prepare: Vec<audio::Source>,
+    // Sounds referenced by name from map data
+    named: HashMap<String, audio::Source>,
 }
 
 impl Sounds {
@@ -75,12 +82,17 @@ impl Sounds {
             break1: audio::Source::new(ctx, "/sound/barrel_break.wav").unwrap(),
             break2: audio::Source::new(ctx, "/sound/crate_break.wav").unwrap(),
             swat_gogogo: audio::Source::new(ctx, "/voice/swat_gogogo.ogg").unwrap(),
+            reinforcements: audio::Source::new(ctx, "/voice/swat4.ogg").unwrap(),
+            radio: audio::Source::new(ctx, "/sound/yee_haw.wav").unwrap(),
+            grenade: audio::Source::new(ctx, "/sound/metal_crunch.wav").unwrap(),
+            pickup: audio::Source::new(ctx, "/sound/whip.wav").unwrap(),
             taunts,
             swat,
             crash,
             ricochet,
             bullet_hit,
             prepare,
+            named: HashMap::new(),
         }
     }
 
@@ -139,6 +151,22 @@ impl Sounds {
         self.swat_gogogo.play().unwrap();
     }
 
+    pub fn play_reinforcements(&mut self) {
+        self.reinforcements.play().unwrap();
+    }
+
+    pub fn play_radio(&mut self) {
+        self.radio.play().unwrap();
+    }
+
+    pub fn play_grenade(&mut self) {
+        self.grenade.play().unwrap();
+    }
+
+    pub fn play_pickup(&mut self) {
+        self.pickup.play().unwrap();
+    }
+
     pub fn play_death(&mut self) {
         self.death.play().unwrap();
     }
@@ -150,6 +178,21 @@ impl Sounds {
     pub fn play_break2(&mut self) {
         self.break2.play().unwrap();
     }
+
+    pub fn load_named(&mut self, ctx: &mut Context, path: &str) {
+        if !self.named.contains_key(path) {
+            let source =
+                audio::Source::new(ctx, path).expect(&format!("failed to load sound {}", path));
+            self.named.insert(path.to_owned(), source);
+        }
+    }
+
+    pub fn play_named(&mut self, path: &str) {
+        if let Some(snd) = self.named.get_mut(path) {
+            snd.set_volume(1.0);
+            snd.play().unwrap();
+        }
+    }
 }
 
 pub fn _inverse_distance(
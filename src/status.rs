@@ -0,0 +1,164 @@
+use crate::Color;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusKind {
+    Burning,
+    Slowed,
+    Stunned,
+    Blinded,
+    Bleeding,
+}
+
+impl StatusKind {
+    // Multiplied into the sprite color while the effect lasts
+    fn tint(self) -> Color {
+        match self {
+            StatusKind::Burning => Color::new(1.0, 0.55, 0.3, 1.0),
+            StatusKind::Slowed => Color::new(0.6, 0.75, 1.0, 1.0),
+            StatusKind::Stunned => Color::new(1.0, 1.0, 0.5, 1.0),
+            StatusKind::Blinded => Color::new(0.6, 0.6, 0.6, 1.0),
+            StatusKind::Bleeding => Color::new(1.0, 0.35, 0.35, 1.0),
+        }
+    }
+
+    // Fraction of top speed kept while the effect lasts, unless its config says otherwise
+    fn speed_scale(self) -> f32 {
+        match self {
+            StatusKind::Slowed => 0.5,
+            StatusKind::Stunned => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stacking {
+    // Reapplying resets the timer to the longer of the two
+    Refresh,
+    // Reapplying adds the new duration on top
+    Extend,
+    // Reapplying adds a stack, multiplying the tick damage, and resets the timer
+    Stack,
+}
+
+impl Default for Stacking {
+    fn default() -> Self {
+        Stacking::Refresh
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct StatusEffectConfig {
+    pub kind: StatusKind,
+    pub seconds: f32,
+    #[serde(default)]
+    pub damage_per_second: f32,
+    #[serde(default)]
+    pub stacking: Stacking,
+    // Cap for `stack`; unlimited when absent
+    #[serde(default)]
+    pub max_stacks: Option<u32>,
+    // Fraction of top speed kept; defaults to 0.5 for `slowed`, 0 for `stunned` and 1 otherwise
+    #[serde(default)]
+    pub speed_scale: Option<f32>,
+}
+
+impl StatusEffectConfig {
+    fn speed_scale(&self) -> f32 {
+        self.speed_scale.unwrap_or_else(|| self.kind.speed_scale())
+    }
+}
+
+struct StatusEffect {
+    kind: StatusKind,
+    seconds_left: f32,
+    damage_per_second: f32,
+    speed_scale: f32,
+    stacks: u32,
+}
+
+#[derive(Default)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, cfg: &StatusEffectConfig) {
+        let existing = self
+            .effects
+            .iter_mut()
+            .find(|effect| effect.kind == cfg.kind);
+
+        match existing {
+            Some(effect) => {
+                match cfg.stacking {
+                    Stacking::Refresh => effect.seconds_left = effect.seconds_left.max(cfg.seconds),
+                    Stacking::Extend => effect.seconds_left += cfg.seconds,
+                    Stacking::Stack => {
+                        if cfg.max_stacks.map_or(true, |max| effect.stacks < max) {
+                            effect.stacks += 1;
+                        }
+                        effect.seconds_left = cfg.seconds;
+                    }
+                }
+                effect.damage_per_second = effect.damage_per_second.max(cfg.damage_per_second);
+                effect.speed_scale = effect.speed_scale.min(cfg.speed_scale());
+            }
+            None => self.effects.push(StatusEffect {
+                kind: cfg.kind,
+                seconds_left: cfg.seconds,
+                damage_per_second: cfg.damage_per_second,
+                speed_scale: cfg.speed_scale(),
+                stacks: 1,
+            }),
+        }
+    }
+
+    // Ticks every effect down, returning the damage dealt this step
+    pub fn update(&mut self, dt: f32) -> f32 {
+        let mut damage = 0.0;
+        for effect in self.effects.iter_mut() {
+            damage += effect.damage_per_second * effect.stacks as f32 * dt.min(effect.seconds_left);
+            effect.seconds_left -= dt;
+        }
+
+        self.effects.retain(|effect| effect.seconds_left > 0.0);
+        damage
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn stunned(&self) -> bool {
+        self.has(StatusKind::Stunned)
+    }
+
+    pub fn blinded(&self) -> bool {
+        self.has(StatusKind::Blinded)
+    }
+
+    pub fn speed_scale(&self) -> f32 {
+        self.effects
+            .iter()
+            .fold(1.0, |scale, effect| scale.min(effect.speed_scale))
+    }
+
+    pub fn tint(&self, color: Color) -> Color {
+        self.effects.iter().fold(color, |color, effect| {
+            let tint = effect.kind.tint();
+            Color::new(
+                color.r * tint.r,
+                color.g * tint.g,
+                color.b * tint.b,
+                color.a,
+            )
+        })
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{Ball, Cuboid, Isometry2, Point2, ShapeHandle, Vector2};
+use ncollide2d::shape::ConvexPolygon;
+use std::collections::HashMap;
+use std::io::Read;
+use tiled::{ObjectShape, Properties, PropertyValue};
+
+// Used by tiles that don't name a material
+const DEFAULT_MATERIAL: &str = "wood";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TileShape {
+    Box,
+    Ball,
+}
+
+// Physical behavior of a tile, from its custom properties in the tileset
+#[derive(Clone)]
+pub struct TileProperties {
+    pub collidable: bool,
+    pub shape: TileShape,
+    pub mass: f32,
+    pub hp: f32,
+    pub friction: f32,
+    pub restitution: f32,
+    pub destructible: bool,
+    pub breaking_sound: Option<String>,
+    pub stiffness: f32,
+    pub damage_threshold: f32,
+    pub damage_scale: f32,
+    // Collision objects drawn on the tile in Tiled, relative to the tile center; these
+    // replace `shape` when present
+    pub shapes: Vec<(Isometry2<f32>, ShapeHandle<f32>)>,
+}
+
+impl Default for TileProperties {
+    fn default() -> Self {
+        TileProperties {
+            collidable: true,
+            shape: TileShape::Box,
+            mass: 10.0,
+            hp: 1.0,
+            friction: 0.0,
+            restitution: 0.3,
+            destructible: true,
+            breaking_sound: None,
+            stiffness: 100.0,
+            damage_threshold: 4.0,
+            damage_scale: 0.1,
+            shapes: Vec::new(),
+        }
+    }
+}
+
+// Defaults shared by every tile of a material, from `resources/materials.toml`
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WallMaterial {
+    pub mass: f32,
+    pub hp: f32,
+    pub friction: f32,
+    pub restitution: f32,
+    // Spring pulling a piece back to where it was placed
+    pub stiffness: f32,
+    // Velocity change contacts force on a piece before it starts breaking, added up over
+    // the whole contact; past it, each unit deals `damage_scale` damage
+    pub damage_threshold: f32,
+    pub damage_scale: f32,
+    pub breaking_sound: Option<String>,
+}
+
+impl Default for WallMaterial {
+    fn default() -> Self {
+        let tile = TileProperties::default();
+        WallMaterial {
+            mass: tile.mass,
+            hp: tile.hp,
+            friction: tile.friction,
+            restitution: tile.restitution,
+            stiffness: tile.stiffness,
+            damage_threshold: tile.damage_threshold,
+            damage_scale: tile.damage_scale,
+            breaking_sound: None,
+        }
+    }
+}
+
+pub struct WallMaterials {
+    materials: HashMap<String, WallMaterial>,
+}
+
+impl WallMaterials {
+    pub fn from_toml(path: &str) -> Self {
+        Self::try_from_toml(path).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_from_toml(path: &str) -> Result<Self, String> {
+        let mut file = std::fs::File::open(path)
+            .map_err(|err| format!("failed to open materials {}: {}", path, err))?;
+        let mut toml = String::new();
+        file.read_to_string(&mut toml)
+            .map_err(|err| format!("failed to open materials {}: {}", path, err))?;
+
+        Ok(WallMaterials {
+            materials: toml::from_str(&toml)
+                .map_err(|err| format!("failed to parse materials {}: {}", path, err))?,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> WallMaterial {
+        self.materials.get(name).cloned().unwrap_or_default()
+    }
+}
+
+impl TileProperties {
+    fn from_material(material: WallMaterial) -> Self {
+        TileProperties {
+            mass: material.mass,
+            hp: material.hp,
+            friction: material.friction,
+            restitution: material.restitution,
+            stiffness: material.stiffness,
+            damage_threshold: material.damage_threshold,
+            damage_scale: material.damage_scale,
+            breaking_sound: material.breaking_sound,
+            ..TileProperties::default()
+        }
+    }
+
+    // The tile's `material` provides the defaults; any other property overrides them
+    fn from_properties(properties: &Properties, materials: &WallMaterials) -> Self {
+        let float = |name: &str, default: f32| match properties.get(name) {
+            Some(PropertyValue::FloatValue(value)) => *value,
+            Some(PropertyValue::IntValue(value)) => *value as f32,
+            _ => default,
+        };
+
+        let boolean = |name: &str, default: bool| match properties.get(name) {
+            Some(PropertyValue::BoolValue(value)) => *value,
+            _ => default,
+        };
+
+        let string = |name: &str| match properties.get(name) {
+            Some(PropertyValue::StringValue(value)) if !value.is_empty() => Some(value.clone()),
+            _ => None,
+        };
+
+        let material = string("material").unwrap_or_else(|| DEFAULT_MATERIAL.to_owned());
+        let defaults = TileProperties::from_material(materials.get(&material));
+
+        TileProperties {
+            collidable: boolean("collidable", defaults.collidable),
+            shape: match string("shape").as_ref().map(String::as_str) {
+                Some("ball") => TileShape::Ball,
+                _ => TileShape::Box,
+            },
+            mass: float("mass", defaults.mass),
+            hp: float("hp", defaults.hp),
+            friction: float("friction", defaults.friction),
+            restitution: float("restitution", defaults.restitution),
+            destructible: boolean("destructible", defaults.destructible),
+            breaking_sound: string("breaking_sound").or(defaults.breaking_sound),
+            stiffness: float("stiffness", defaults.stiffness),
+            damage_threshold: float("damage_threshold", defaults.damage_threshold),
+            damage_scale: float("damage_scale", defaults.damage_scale),
+            shapes: Vec::new(),
+        }
+    }
+}
+
+// Converts the collision objects of a tile from tileset pixels (origin at the tile's
+// top-left corner, y down) to world units around the tile center
+fn collision_shapes(
+    map: &tiled::Map,
+    tileset: &tiled::Tileset,
+    group: &tiled::ObjectGroup,
+) -> Vec<(Isometry2<f32>, ShapeHandle<f32>)> {
+    let scale = 1.0 / map.tile_width as f32;
+    let half_w = tileset.tile_width as f32 * 0.5;
+    let half_h = tileset.tile_height as f32 * 0.5;
+
+    let mut shapes = Vec::new();
+    for object in &group.objects {
+        // Tiled rotates objects clockwise around their origin
+        let angle = object.rotation.to_radians();
+        let (sin, cos) = angle.sin_cos();
+        let to_world = |x: f32, y: f32| {
+            let px = object.x + x * cos - y * sin;
+            let py = object.y + x * sin + y * cos;
+            Point2::new((px - half_w) * scale, (half_h - py) * scale)
+        };
+
+        match object.shape {
+            ObjectShape::Rect { width, height } => {
+                let center = to_world(width * 0.5, height * 0.5);
+                let half_extents = Vector2::new(width * 0.5 * scale, height * 0.5 * scale);
+                shapes.push((
+                    Isometry2::new(center.coords, -angle),
+                    ShapeHandle::new(Cuboid::new(half_extents)),
+                ));
+            }
+            ObjectShape::Ellipse { width, height } => {
+                let center = to_world(width * 0.5, height * 0.5);
+                let radius = width.min(height) * 0.5 * scale;
+                shapes.push((
+                    Isometry2::new(center.coords, 0.0),
+                    ShapeHandle::new(Ball::new(radius)),
+                ));
+            }
+            ObjectShape::Polygon { ref points } => {
+                let points: Vec<Point2> = points.iter().map(|&(x, y)| to_world(x, y)).collect();
+                if let Some(polygon) = ConvexPolygon::try_from_points(&points) {
+                    shapes.push((Isometry2::identity(), ShapeHandle::new(polygon)));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    shapes
+}
+
+// Properties of every tile that sets any, keyed by global tile id
+pub struct TileCatalog {
+    tiles: HashMap<u32, TileProperties>,
+    // For tiles without properties
+    default: TileProperties,
+}
+
+impl TileCatalog {
+    pub fn from_map(map: &tiled::Map, materials: &WallMaterials) -> Self {
+        let mut tiles = HashMap::new();
+
+        for tileset in &map.tilesets {
+            for tile in &tileset.tiles {
+                if tile.properties.is_empty() && tile.objectgroup.is_none() {
+                    continue;
+                }
+
+                let mut properties = TileProperties::from_properties(&tile.properties, materials);
+                if let Some(ref group) = tile.objectgroup {
+                    properties.shapes = collision_shapes(map, tileset, group);
+                }
+
+                tiles.insert(tileset.first_gid + tile.id, properties);
+            }
+        }
+
+        TileCatalog {
+            tiles,
+            default: TileProperties::from_material(materials.get(DEFAULT_MATERIAL)),
+        }
+    }
+
+    pub fn get(&self, gid: u32) -> TileProperties {
+        self.find(gid)
+            .cloned()
+            .unwrap_or_else(|| self.default.clone())
+    }
+
+    // Only tiles the tileset says something about; used where untagged tiles are decoration
+    pub fn find(&self, gid: u32) -> Option<&TileProperties> {
+        self.tiles.get(&gid)
+    }
+
+    pub fn breaking_sounds(&self) -> Vec<String> {
+        let mut sounds: Vec<String> = self
+            .tiles
+            .values()
+            .filter_map(|tile| tile.breaking_sound.clone())
+            .collect();
+        sounds.sort();
+        sounds.dedup();
+        sounds
+    }
+}
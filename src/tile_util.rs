@@ -1,9 +1,31 @@
 use super::types::*;
-use crate::{DrawParam, Image, Rect, SpriteBatch};
+use crate::{graphics, Color, Context, DrawParam, Image, Rect, SpriteBatch, TileCatalog};
 use nalgebra as na;
+use std::collections::{HashMap, VecDeque};
+use std::io::BufReader;
+use std::path::Path;
+use xml::reader::{EventReader, XmlEvent};
+
+// Tiled stores flip flags in the top bits of each gid
+pub const GID_MASK: u32 = 0x1fff_ffff;
+
+// Tile layers are pre-batched in square chunks of this many tiles, so drawing only
+// touches the chunks on screen
+const CHUNK_TILES: u32 = 16;
+
+// Size of one map cell in world units; a world unit spans the map's tile width
+pub fn tile_cell(map: &tiled::Map) -> Vector2 {
+    Vector2::new(1.0, map.tile_height as f32 / map.tile_width as f32)
+}
+
+// World units per tileset pixel
+pub fn tile_px_scale(map: &tiled::Map) -> f32 {
+    1.0 / map.tile_width as f32
+}
 
 pub struct TileMapLayerView<'a> {
     pub layer: &'a tiled::Layer,
+    pub cell: Vector2,
     pub start_x: u32,
     pub end_x: u32,
     pub start_y: u32,
@@ -19,11 +41,12 @@ pub struct TileMapLayerViewIterator<'a> {
 }
 
 impl<'a> TileMapLayerView<'a> {
-    pub fn new(layer: &'a tiled::Layer) -> Self {
+    pub fn new(map: &tiled::Map, layer: &'a tiled::Layer) -> Self {
         Self {
             layer,
+            cell: tile_cell(map),
             start_x: 0,
-            end_x: layer.tiles[0].len() as u32,
+            end_x: layer.tiles.first().map_or(0, Vec::len) as u32,
             start_y: 0,
             end_y: layer.tiles.len() as u32,
         }
@@ -34,7 +57,7 @@ impl<'a> TileMapLayerView<'a> {
             view: self,
             x: self.start_x as i32 - 1,
             y: self.start_y as i32,
-            x_offset: self.layer.tiles[0].len() as f32 * -0.5,
+            x_offset: self.layer.tiles.first().map_or(0, Vec::len) as f32 * -0.5,
             y_offset: self.layer.tiles.len() as f32 * -0.5,
         }
     }
@@ -42,6 +65,7 @@ impl<'a> TileMapLayerView<'a> {
 
 #[derive(Clone)]
 pub struct MapTile {
+    // Global tile id, resolved against the map's tilesets by `MapGraphics`
     pub tile_id: u32,
     pub pos: Point2,
 }
@@ -60,88 +84,473 @@ impl<'a> Iterator for TileMapLayerViewIterator<'a> {
             }
 
             if self.y < self.view.end_y as i32 {
-                let tile = self.view.layer.tiles[map_size_y - 1 - self.y as usize][self.x as usize];
+                let tile = self.view.layer.tiles[map_size_y - 1 - self.y as usize][self.x as usize]
+                    & GID_MASK;
                 if tile != 0 {
-                    break Some((self.x as f32, self.y as f32, tile - 1));
+                    break Some((self.x as f32, self.y as f32, tile));
                 }
             } else {
                 break None;
             }
         };
 
-        // TODO: get actual map size
+        let cell = self.view.cell;
         res.map(|(x, y, tile_id)| MapTile {
-            pos: {
-                let tile_size = 64.0; // TODO
-                let scale = 1.0 / tile_size;
+            pos: Point2::new((x + self.x_offset) * cell.x, (y + self.y_offset) * cell.y),
+            tile_id,
+        })
+    }
+}
 
-                let x = (x + self.x_offset) * tile_size;
-                let y = (y + self.y_offset) * tile_size;
+// Tileset images live at the root of `resources`
+pub fn tileset_image_path(tileset: &tiled::Tileset) -> Option<String> {
+    tileset.images.first().map(|image| {
+        if image.source.starts_with('/') {
+            image.source.clone()
+        } else {
+            let name = Path::new(&image.source)
+                .file_name()
+                .map_or(String::new(), |name| name.to_string_lossy().into_owned());
+            format!("/{}", name)
+        }
+    })
+}
 
-                Point2::new(x as f32 * scale, y as f32 * scale)
-            },
-            tile_id,
+pub struct MapTileset {
+    pub first_gid: u32,
+    tile_width: u32,
+    tile_height: u32,
+    spacing: u32,
+    margin: u32,
+    columns: u32,
+    rows: u32,
+    image: Image,
+    batch: SpriteBatch,
+}
+
+impl MapTileset {
+    // Tilesets without a usable image are left out, so their tiles aren't drawn;
+    // `--validate-map` says what is wrong with them
+    fn load(ctx: &mut Context, tileset: &tiled::Tileset) -> Option<Self> {
+        let path = tileset_image_path(tileset)?;
+        let image = Image::new(ctx, &path).ok()?;
+
+        let span = |image_size: u16, tile_size: u32| {
+            (image_size as u32)
+                .checked_sub(tileset.margin.checked_mul(2)?)?
+                .checked_add(tileset.spacing)?
+                .checked_div(tile_size.checked_add(tileset.spacing)?)
+        };
+        let columns = span(image.width(), tileset.tile_width)?;
+        let rows = span(image.height(), tileset.tile_height)?;
+
+        Some(MapTileset {
+            first_gid: tileset.first_gid,
+            tile_width: tileset.tile_width,
+            tile_height: tileset.tile_height,
+            spacing: tileset.spacing,
+            margin: tileset.margin,
+            columns: columns.max(1),
+            rows: rows.max(1),
+            batch: SpriteBatch::new(image.clone()),
+            image,
         })
     }
+
+    fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid - self.first_gid < self.columns * self.rows
+    }
+
+    fn sprite_param(&self, map: &tiled::Map, gid: u32, param: DrawParam) -> DrawParam {
+        let scale = tile_px_scale(map);
+        param
+            .src(self.src_rect(gid))
+            .scale(Vector2::new(scale, -scale))
+            .offset(Point2::new(0.5, 0.5))
+    }
+
+    fn src_rect(&self, gid: u32) -> Rect {
+        let local = gid - self.first_gid;
+        let col = local % self.columns;
+        let row = local / self.columns;
+
+        let image_w = self.image.width() as f32;
+        let image_h = self.image.height() as f32;
+
+        Rect::new(
+            (self.margin + col * (self.tile_width + self.spacing)) as f32 / image_w,
+            (self.margin + row * (self.tile_height + self.spacing)) as f32 / image_h,
+            self.tile_width as f32 / image_w,
+            self.tile_height as f32 / image_h,
+        )
+    }
+}
+
+// A tile layer baked into sprite batches when the map is loaded. Chunks are stored
+// row-major, counted from the bottom-left like `TileMapLayerView`, each with one batch
+// per tileset it uses.
+struct CachedLayer {
+    chunks_x: u32,
+    chunks_y: u32,
+    chunks: Vec<Vec<SpriteBatch>>,
+}
+
+// Images and sprite batches for every tileset of the map, plus the per-layer offsets
+// Tiled stores on the layers themselves
+pub struct MapGraphics {
+    tilesets: Vec<MapTileset>,
+    layer_offsets: HashMap<String, Vector2>,
+    layers: HashMap<String, CachedLayer>,
 }
 
-pub fn tile_id_to_src_rect(tile: u32, map: &tiled::Map, image: &Image) -> Rect {
-    let tile_width = map.tile_width;
-    let tile_height = map.tile_height;
+impl MapGraphics {
+    pub fn load(ctx: &mut Context, map: &tiled::Map, path: &Path) -> Self {
+        let mut tilesets: Vec<MapTileset> = map
+            .tilesets
+            .iter()
+            .filter_map(|tileset| MapTileset::load(ctx, tileset))
+            .collect();
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut map_graphics = MapGraphics {
+            tilesets,
+            layer_offsets: read_layer_offsets(path).expect("failed to read layer offsets"),
+            layers: HashMap::new(),
+        };
+
+        for layer in &map.layers {
+            let cached = map_graphics.bake_layer(map, layer);
+            map_graphics.layers.insert(layer.name.clone(), cached);
+        }
+
+        map_graphics
+    }
+
+    fn bake_layer(&self, map: &tiled::Map, layer: &tiled::Layer) -> CachedLayer {
+        let chunks_x =
+            (layer.tiles.first().map_or(0, Vec::len) as u32 + CHUNK_TILES - 1) / CHUNK_TILES;
+        let chunks_y = (layer.tiles.len() as u32 + CHUNK_TILES - 1) / CHUNK_TILES;
+        let mut chunks = Vec::new();
+
+        for chunk_y in 0..chunks_y {
+            for chunk_x in 0..chunks_x {
+                chunks.push(self.bake_chunk(map, layer, chunk_x, chunk_y));
+            }
+        }
+
+        CachedLayer {
+            chunks_x,
+            chunks_y,
+            chunks,
+        }
+    }
+
+    fn bake_chunk(
+        &self,
+        map: &tiled::Map,
+        layer: &tiled::Layer,
+        chunk_x: u32,
+        chunk_y: u32,
+    ) -> Vec<SpriteBatch> {
+        let view = TileMapLayerView::new(map, layer);
+        let half_cell = view.cell * 0.5;
+        let offset = self.layer_offset(map, &layer.name);
+        let color = Color::new(1.0, 1.0, 1.0, layer.opacity);
+
+        let chunk_view = TileMapLayerView {
+            layer,
+            cell: view.cell,
+            start_x: chunk_x * CHUNK_TILES,
+            end_x: ((chunk_x + 1) * CHUNK_TILES).min(view.end_x),
+            start_y: chunk_y * CHUNK_TILES,
+            end_y: ((chunk_y + 1) * CHUNK_TILES).min(view.end_y),
+        };
+
+        let mut batches: Vec<Option<SpriteBatch>> = self.tilesets.iter().map(|_| None).collect();
+
+        for MapTile { tile_id, pos } in chunk_view.iter() {
+            if let Some(i) = self.tileset_index(tile_id) {
+                let tileset = &self.tilesets[i];
+                let param = DrawParam::new().dest(pos + offset - half_cell).color(color);
+                batches[i]
+                    .get_or_insert_with(|| SpriteBatch::new(tileset.image.clone()))
+                    .add(tileset.sprite_param(map, tile_id, param));
+            }
+        }
+
+        batches.into_iter().flatten().collect()
+    }
+
+    // Rebuilds the cached chunk holding a tile after the layer was edited; `tile_y` counts
+    // rows from the bottom like `TileMapLayerView`
+    pub fn rebake_tile(&mut self, map: &tiled::Map, layer_name: &str, tile_x: u32, tile_y: u32) {
+        let layer = match get_map_layer(map, layer_name) {
+            Some(layer) => layer,
+            None => return,
+        };
+
+        let (chunk_x, chunk_y) = (tile_x / CHUNK_TILES, tile_y / CHUNK_TILES);
+        let batches = self.bake_chunk(map, layer, chunk_x, chunk_y);
+
+        if let Some(cached) = self.layers.get_mut(layer_name) {
+            let index = (chunk_y * cached.chunks_x + chunk_x) as usize;
+            cached.chunks[index] = batches;
+        }
+    }
+
+    // Every gid the map's tilesets provide
+    pub fn tile_gids(&self) -> Vec<u32> {
+        self.tilesets
+            .iter()
+            .flat_map(|tileset| {
+                tileset.first_gid..tileset.first_gid + tileset.columns * tileset.rows
+            })
+            .collect()
+    }
+
+    // Tiles of a tileset that was left out belong to none
+    fn tileset_index(&self, gid: u32) -> Option<usize> {
+        self.tilesets
+            .iter()
+            .rposition(|tileset| tileset.first_gid <= gid)
+            .filter(|&i| self.tilesets[i].contains(gid))
+    }
 
-    let tile_w = tile_width as f32 / image.width() as f32;
-    let tile_h = tile_height as f32 / image.height() as f32;
+    // Gid of a tile in the map's first tileset, for tiles placed by the game itself
+    pub fn first_tileset_gid(&self, local_id: u32) -> u32 {
+        self.tilesets.first().map_or(1, |tileset| tileset.first_gid) + local_id
+    }
 
-    let tile_column_count = (image.width() as usize) / (tile_width as usize);
+    // Offset of a tile layer in world units
+    pub fn layer_offset(&self, map: &tiled::Map, layer_name: &str) -> Vector2 {
+        let scale = tile_px_scale(map);
+        self.layer_offsets
+            .get(layer_name)
+            .map_or(Vector2::zeros(), |px| {
+                Vector2::new(px.x * scale, -px.y * scale)
+            })
+    }
 
-    let tile_c = (tile as usize % tile_column_count) as f32;
-    let tile_r = (tile as usize / tile_column_count) as f32;
+    pub fn add(&mut self, map: &tiled::Map, gid: u32, param: DrawParam) {
+        if let Some(i) = self.tileset_index(gid) {
+            let tileset = &mut self.tilesets[i];
+            let param = tileset.sprite_param(map, gid, param);
+            tileset.batch.add(param);
+        }
+    }
 
-    Rect::new(tile_w * tile_c, tile_h * tile_r, tile_w, tile_h)
+    pub fn draw_and_clear(&mut self, ctx: &mut Context) {
+        for tileset in self.tilesets.iter_mut() {
+            graphics::draw(ctx, &tileset.batch, DrawParam::new()).unwrap();
+            tileset.batch.clear();
+        }
+    }
 }
 
-pub fn get_map_layer<'a>(map: &'a tiled::Map, layer_name: &str) -> &'a tiled::Layer {
-    let layer_idx = map
-        .layers
-        .iter()
-        .position(|layer| layer.name == layer_name)
-        .unwrap();
+// The tiled crate drops `offsetx`/`offsety` on tile layers, so read them from the file
+// again. Layers inside groups add up the offsets of the groups around them.
+pub fn read_layer_offsets(path: &Path) -> Result<HashMap<String, Vector2>, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|err| format!("failed to open map {}: {}", path.display(), err))?;
+
+    let mut offsets = HashMap::new();
+    let mut groups = vec![Vector2::zeros()];
+
+    for event in EventReader::new(BufReader::new(file)) {
+        match event.map_err(|err| format!("failed to parse map {}: {}", path.display(), err))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let attribute = |key: &str| {
+                    attributes
+                        .iter()
+                        .find(|attribute| attribute.name.local_name == key)
+                        .map(|attribute| attribute.value.as_str())
+                };
+                let coordinate = |key: &str| {
+                    attribute(key)
+                        .and_then(|v| v.parse::<f32>().ok())
+                        .unwrap_or(0.0)
+                };
+                let offset = groups.last().unwrap()
+                    + Vector2::new(coordinate("offsetx"), coordinate("offsety"));
+
+                match name.local_name.as_str() {
+                    "group" => groups.push(offset),
+                    "layer" => {
+                        if let Some(layer_name) = attribute("name") {
+                            if offset != Vector2::zeros() {
+                                offsets.insert(layer_name.to_owned(), offset);
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            XmlEvent::EndElement { ref name } if name.local_name == "group" => {
+                groups.pop();
+            }
+            _ => (),
+        }
+    }
+
+    Ok(offsets)
+}
 
-    &map.layers[layer_idx]
+// Every layer is optional; maps without one simply skip whatever it would provide
+pub fn get_map_layer<'a>(map: &'a tiled::Map, layer_name: &str) -> Option<&'a tiled::Layer> {
+    map.layers.iter().find(|layer| layer.name == layer_name)
 }
 
 // Inspired by https://github.com/FloVanGH/pg-engine/blob/master/src/drawing.rs
-pub fn draw_map_layer(batch: &mut SpriteBatch, map: &tiled::Map, image: &Image, layer_name: &str) {
-    //let map = &self.map;
-    let layer = get_map_layer(map, layer_name);
-
-    let tile_width = map.tile_width;
-    let scale = 1.0 / tile_width as f32;
-
-    let start_column = 0;
-    let start_row = 0;
-    let end_column = map.width;
-    let end_row = map.height;
-
-    // TODO: figure out the extents to draw
-    let view = TileMapLayerView {
-        layer,
-        start_x: start_column,
-        end_x: end_column,
-        start_y: start_row,
-        end_y: end_row,
+pub fn draw_map_layer(
+    ctx: &mut Context,
+    map_graphics: &MapGraphics,
+    map: &tiled::Map,
+    layer_name: &str,
+    screen_to_world: Matrix4,
+) {
+    let cached = match get_map_layer(map, layer_name) {
+        Some(layer) if layer.visible => &map_graphics.layers[layer_name],
+        _ => return,
     };
 
-    for MapTile { tile_id, pos } in view.iter() {
-        let src = tile_id_to_src_rect(tile_id, map, image);
-        batch.add(
-            DrawParam::new()
-                .src(src)
-                .dest(pos - Vector2::new(0.5, 0.5))
-                .scale(Vector2::new(scale, -scale))
-                .offset(Point2::new(0.5, 0.5)),
-        );
+    // World space rectangle covered by the window, in the layer's own frame
+    let (screen_w, screen_h) = graphics::drawable_size(ctx);
+    let offset = map_graphics.layer_offset(map, layer_name);
+    let corners: Vec<Point2> = [
+        (0.0, 0.0),
+        (screen_w, 0.0),
+        (0.0, screen_h),
+        (screen_w, screen_h),
+    ]
+    .iter()
+    .map(|&(x, y)| px_to_world(screen_to_world, x, y) - offset)
+    .collect();
+
+    let min = corners
+        .iter()
+        .fold(corners[0], |a, b| Point2::new(a.x.min(b.x), a.y.min(b.y)));
+    let max = corners
+        .iter()
+        .fold(corners[0], |a, b| Point2::new(a.x.max(b.x), a.y.max(b.y)));
+
+    // Tile indices on screen, matching the placement of `TileMapLayerViewIterator`
+    let cell = tile_cell(map);
+    let to_tile = |v: f32, cell: f32, count: u32| v / cell + count as f32 * 0.5;
+    let start_x = (to_tile(min.x, cell.x, map.width) - 0.5).floor().max(0.0) as u32;
+    let start_y = (to_tile(min.y, cell.y, map.height) - 0.5).floor().max(0.0) as u32;
+    let end_x = (to_tile(max.x, cell.x, map.width) + 0.5).ceil().max(0.0) as u32;
+    let end_y = (to_tile(max.y, cell.y, map.height) + 0.5).ceil().max(0.0) as u32;
+
+    let chunks_x =
+        (start_x / CHUNK_TILES)..((end_x + CHUNK_TILES - 1) / CHUNK_TILES).min(cached.chunks_x);
+    let chunks_y =
+        (start_y / CHUNK_TILES)..((end_y + CHUNK_TILES - 1) / CHUNK_TILES).min(cached.chunks_y);
+
+    for chunk_y in chunks_y {
+        for chunk_x in chunks_x.clone() {
+            for batch in &cached.chunks[(chunk_y * cached.chunks_x + chunk_x) as usize] {
+                graphics::draw(ctx, batch, DrawParam::new()).unwrap();
+            }
+        }
+    }
+}
+
+// Converts Tiled pixel coordinates (origin top-left, y down) to world space, matching
+// the tile placement of `TileMapLayerViewIterator`.
+pub fn map_px_to_world(map: &tiled::Map, x: f32, y: f32) -> Point2 {
+    let cell = tile_cell(map);
+    let tile_x = x / map.tile_width as f32;
+    let tile_y = y / map.tile_height as f32;
+    Point2::new(
+        (tile_x - map.width as f32 * 0.5 - 0.5) * cell.x,
+        (map.height as f32 * 0.5 - 0.5 - tile_y) * cell.y,
+    )
+}
+
+// World space corners of the map (bottom-left, top-right)
+pub fn map_bounds(map: &tiled::Map) -> (Point2, Point2) {
+    let width_px = (map.width * map.tile_width) as f32;
+    let height_px = (map.height * map.tile_height) as f32;
+    (
+        map_px_to_world(map, 0.0, height_px),
+        map_px_to_world(map, width_px, 0.0),
+    )
+}
+
+// Inverse of `map_px_to_world`
+pub fn world_to_map_px(map: &tiled::Map, pos: Point2) -> (f32, f32) {
+    let cell = tile_cell(map);
+    let tile_x = pos.x / cell.x + map.width as f32 * 0.5 + 0.5;
+    let tile_y = map.height as f32 * 0.5 - 0.5 - pos.y / cell.y;
+    (
+        tile_x * map.tile_width as f32,
+        tile_y * map.tile_height as f32,
+    )
+}
+
+// Column and row of the map cell under `pos`, if it's on the map
+pub fn world_to_map_cell(map: &tiled::Map, pos: Point2) -> Option<(usize, usize)> {
+    let (x, y) = world_to_map_px(map, pos);
+    let column = (x / map.tile_width as f32).floor();
+    let row = (y / map.tile_height as f32).floor();
+
+    if column < 0.0 || row < 0.0 || column >= map.width as f32 || row >= map.height as f32 {
+        None
+    } else {
+        Some((column as usize, row as usize))
+    }
+}
+
+// Cells covered by props nothing can get through, indexed `[row][column]`
+pub fn solid_prop_cells(map: &tiled::Map, catalog: &TileCatalog) -> Vec<Vec<bool>> {
+    let mut solid = vec![vec![false; map.width as usize]; map.height as usize];
+
+    if let Some(layer) = get_map_layer(map, "Props") {
+        for (row, tiles) in layer.tiles.iter().enumerate().take(map.height as usize) {
+            for (column, &gid) in tiles.iter().enumerate().take(map.width as usize) {
+                let gid = gid & GID_MASK;
+                if gid != 0 {
+                    solid[row][column] = catalog.find(gid).map_or(false, |tile| tile.collidable);
+                }
+            }
+        }
+    }
+
+    solid
+}
+
+// Flood fill over open cells from every cell in `starts`
+pub fn reachable_cells(blocked: &[Vec<bool>], starts: &[(usize, usize)]) -> Vec<Vec<bool>> {
+    let height = blocked.len();
+    let width = blocked.first().map_or(0, |row| row.len());
+    let mut reached = vec![vec![false; width]; height];
+
+    let mut queue = VecDeque::new();
+    for &(x, y) in starts {
+        if x < width && y < height && !blocked[y][x] && !reached[y][x] {
+            reached[y][x] = true;
+            queue.push_back((x, y));
+        }
     }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for &(nx, ny) in neighbors.iter() {
+            if nx < width && ny < height && !blocked[ny][nx] && !reached[ny][nx] {
+                reached[ny][nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    reached
 }
 
 pub fn px_to_world(screen_to_world: Matrix4, x: f32, y: f32) -> Point2 {
@@ -149,3 +558,9 @@ pub fn px_to_world(screen_to_world: Matrix4, x: f32, y: f32) -> Point2 {
         .xy()
         .into()
 }
+
+pub fn world_to_px(world_to_screen: Matrix4, pos: Point2) -> Point2 {
+    (world_to_screen * na::Vector4::new(pos.x, pos.y, 0.0, 1.0))
+        .xy()
+        .into()
+}
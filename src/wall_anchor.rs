@@ -0,0 +1,75 @@
+use crate::{BodyHandle, Force2, Point2, Vector2};
+use nphysics2d::force_generator::ForceGenerator;
+use nphysics2d::object::BodySet;
+use nphysics2d::solver::IntegrationParameters;
+
+// Damping of a piece's movement and spin, per second
+const LINEAR_DAMPING: f32 = 1.2;
+const ANGULAR_DAMPING: f32 = 3.0;
+// How hard a piece is turned back upright, per unit of angular inertia
+const UPRIGHT_STIFFNESS: f32 = 10.0;
+
+// Holds a wall piece where it was placed: a damped spring pulls it back to its anchor
+// and a torsion spring turns it upright again
+pub struct WallAnchor {
+    body: BodyHandle,
+    anchor: Point2,
+    stiffness: f32,
+    mass: f32,
+    angular_inertia: f32,
+}
+
+impl WallAnchor {
+    pub fn new(
+        body: BodyHandle,
+        anchor: Point2,
+        stiffness: f32,
+        mass: f32,
+        angular_inertia: f32,
+    ) -> Self {
+        WallAnchor {
+            body,
+            anchor,
+            stiffness,
+            mass,
+            angular_inertia,
+        }
+    }
+}
+
+// Acceleration the anchor gives a piece at `pos` moving at `velocity`
+pub fn anchor_acceleration(
+    anchor: Point2,
+    stiffness: f32,
+    mass: f32,
+    pos: Point2,
+    velocity: Vector2,
+) -> Vector2 {
+    (anchor - pos) * stiffness / mass - velocity * LINEAR_DAMPING
+}
+
+impl ForceGenerator<f32> for WallAnchor {
+    fn apply(&mut self, _: &IntegrationParameters<f32>, bodies: &mut BodySet<f32>) -> bool {
+        let rb = match bodies.rigid_body_mut(self.body) {
+            Some(rb) => rb,
+            None => return false,
+        };
+
+        let position = rb.position().clone();
+        let velocity = rb.velocity().clone();
+
+        let accel = anchor_acceleration(
+            self.anchor,
+            self.stiffness,
+            self.mass,
+            position.translation.vector.into(),
+            velocity.linear,
+        );
+        let torque = -(position.rotation.angle() * UPRIGHT_STIFFNESS
+            + velocity.angular * ANGULAR_DAMPING)
+            * self.angular_inertia;
+
+        rb.apply_force(&Force2::new(accel * self.mass, torque));
+        true
+    }
+}
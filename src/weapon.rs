@@ -1,15 +1,16 @@
 use super::types::*;
-use crate::Context;
+use crate::{Context, Faction, StatusEffectConfig};
 use ggez::audio;
 use rand::Rng;
 use std::io::Read;
 
 pub struct Bullet {
     pub pos: Positional,
-    pub allegiance: usize,
+    pub faction: Faction,
     pub velocity: f32,
     pub life_seconds: f32,
     pub damage: f32,
+    pub effects: Vec<StatusEffectConfig>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -21,6 +22,12 @@ pub struct WeaponConfig {
     pub fire_rate: f32,
     pub spread_degrees: f32,
     pub sound_file: String,
+    // Rounds before the weapon runs dry; unlimited when absent
+    #[serde(default)]
+    pub ammo: Option<u32>,
+    // Status effects applied to whoever the bullets hit
+    #[serde(default)]
+    pub effects: Vec<StatusEffectConfig>,
 }
 
 impl WeaponConfig {
@@ -37,6 +44,7 @@ impl WeaponConfig {
 pub struct Weapon {
     cfg: WeaponConfig,
     cooldown: f32,
+    ammo: Option<u32>,
     audio_source: audio::Source,
 }
 
@@ -45,6 +53,7 @@ impl Weapon {
         Self {
             cfg: cfg.clone(),
             cooldown: 0.0,
+            ammo: cfg.ammo,
             audio_source: audio::Source::new(ctx, cfg.sound_file).unwrap(),
         }
     }
@@ -53,13 +62,17 @@ impl Weapon {
         &mut self,
         shoot: bool,
         pos: &Positional,
-        allegiance: usize,
+        faction: Faction,
         sink: &mut Vec<Bullet>,
     ) {
         self.cooldown -= 1.0 / 60.0;
-        if shoot && self.cooldown <= 0.0 {
+        if shoot && self.cooldown <= 0.0 && self.ammo != Some(0) {
             self.audio_source.play().unwrap();
 
+            if let Some(ref mut ammo) = self.ammo {
+                *ammo -= 1;
+            }
+
             self.cooldown = 1.0 / self.cfg.fire_rate;
             let mut rng = rand::thread_rng();
             let half_spread_radians = self.cfg.spread_degrees.max(1e-5).to_radians() * 0.5;
@@ -74,9 +87,31 @@ impl Weapon {
                     velocity: self.cfg.bullet_velocity,
                     life_seconds: self.cfg.bullet_life_seconds,
                     damage: self.cfg.bullet_damage,
-                    allegiance,
+                    effects: self.cfg.effects.clone(),
+                    faction,
                 });
             }
         }
     }
 }
+
+impl Weapon {
+    pub fn ammo(&self) -> Option<u32> {
+        self.ammo
+    }
+
+    pub fn out_of_ammo(&self) -> bool {
+        self.ammo == Some(0)
+    }
+
+    // Returns false for weapons that never run dry
+    pub fn add_ammo(&mut self, rounds: u32) -> bool {
+        match self.ammo {
+            Some(ref mut ammo) => {
+                *ammo += rounds;
+                true
+            }
+            None => false,
+        }
+    }
+}